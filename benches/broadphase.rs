@@ -0,0 +1,93 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use physics_engine::{
+    bounding_volume::BoundingVolume,
+    simulation::world::*,
+    vec2::{UNIT_RIGHT, Vec2D, ZERO},
+};
+
+const BROADPHASES: [&str; 3] = ["tree", "sweep and prune", "spatial hash"];
+
+fn set_broadphase(world: &mut World, broadphase: &str) {
+    match broadphase {
+        "tree" => world.set_broadphase(TreeBroadphase::new()),
+        "sweep and prune" => world.set_broadphase(SweepAndPrune::new()),
+        "spatial hash" => world.set_broadphase(SpatialHash::new(20.)),
+        _ => unreachable!(),
+    }
+}
+
+fn broadphase_benchmark(c: &mut Criterion, name: &str, spec: GenerationSpec) {
+    let mut group = c.benchmark_group(name);
+
+    for num_bodies in [100, 1000, 10000] {
+        for broadphase in BROADPHASES {
+            let mut world = World::generate(&GenerationSpec { num_bodies, ..spec });
+            set_broadphase(&mut world, broadphase);
+
+            group.bench_function(BenchmarkId::new(broadphase, num_bodies), |b| {
+                b.iter(|| world.clone().tick(1.))
+            });
+        }
+    }
+}
+
+fn uniform_bodies_benchmark(c: &mut Criterion) {
+    broadphase_benchmark(
+        c,
+        "Broadphase/uniform",
+        GenerationSpec {
+            gravity: ZERO,
+            size: Distribution::Constant(5.),
+            ..GenerationSpec::default()
+        },
+    );
+}
+
+fn varied_bodies_benchmark(c: &mut Criterion) {
+    broadphase_benchmark(
+        c,
+        "Broadphase/varied",
+        GenerationSpec {
+            gravity: ZERO,
+            size: Distribution::Uniform { min: 1., max: 50. },
+            ..GenerationSpec::default()
+        },
+    );
+}
+
+fn query_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Broadphase/query");
+
+    let area = BoundingVolume {
+        top_left: Vec2D { x: 900., y: 500. },
+        bottom_right: Vec2D { x: 1000., y: 600. },
+    };
+
+    for num_bodies in [100, 1000, 10000] {
+        for broadphase in BROADPHASES {
+            let mut world = World::generate(&GenerationSpec {
+                num_bodies,
+                gravity: ZERO,
+                size: Distribution::Constant(5.),
+                ..GenerationSpec::default()
+            });
+            set_broadphase(&mut world, broadphase);
+            world.tick(0.01);
+
+            group.bench_function(BenchmarkId::new(broadphase, num_bodies), |b| {
+                b.iter(|| {
+                    world.bodies_in_aabb(area);
+                    world.raycast(Vec2D { x: 0., y: 540. }, UNIT_RIGHT, 1920., |_| true)
+                })
+            });
+        }
+    }
+}
+
+criterion_group!(
+    broadphase_benches,
+    uniform_bodies_benchmark,
+    varied_bodies_benchmark,
+    query_benchmark
+);
+criterion_main!(broadphase_benches);
@@ -17,25 +17,12 @@ fn generate_contact_benchmark(c: &mut Criterion) {
 }
 
 fn circle_to_circle_colliding_benchmark<M: Measurement>(group: &mut BenchmarkGroup<'_, M>) {
-    let circle_1 = DynamicBody::Circle(Circle {
-        body: BaseDynamicBody {
-            position: ZERO,
-            velocity: ZERO,
-            coefficient_of_restitution: 0.,
-            inverse_mass: 1.,
-        },
-        radius: 5.,
-    });
+    let circle_1 = DynamicBody::Circle(Circle::new(BaseDynamicBody::new(ZERO, ZERO, 0., 1.), 5.));
 
-    let circle_2 = DynamicBody::Circle(Circle {
-        body: BaseDynamicBody {
-            position: UNIT_RIGHT,
-            velocity: ZERO,
-            coefficient_of_restitution: 0.,
-            inverse_mass: 1.,
-        },
-        radius: 5.,
-    });
+    let circle_2 = DynamicBody::Circle(Circle::new(
+        BaseDynamicBody::new(UNIT_RIGHT, ZERO, 0., 1.),
+        5.,
+    ));
 
     group.bench_with_input(
         "circle to circle (colliding)",
@@ -45,25 +32,12 @@ fn circle_to_circle_colliding_benchmark<M: Measurement>(group: &mut BenchmarkGro
 }
 
 fn circle_to_circle_not_colliding_benchmark<M: Measurement>(group: &mut BenchmarkGroup<'_, M>) {
-    let circle_1 = DynamicBody::Circle(Circle {
-        body: BaseDynamicBody {
-            position: ZERO,
-            velocity: ZERO,
-            coefficient_of_restitution: 0.,
-            inverse_mass: 1.,
-        },
-        radius: 5.,
-    });
+    let circle_1 = DynamicBody::Circle(Circle::new(BaseDynamicBody::new(ZERO, ZERO, 0., 1.), 5.));
 
-    let circle_2 = DynamicBody::Circle(Circle {
-        body: BaseDynamicBody {
-            position: Vec2D { x: 10., y: 10. },
-            velocity: ZERO,
-            coefficient_of_restitution: 0.,
-            inverse_mass: 1.,
-        },
-        radius: 5.,
-    });
+    let circle_2 = DynamicBody::Circle(Circle::new(
+        BaseDynamicBody::new(Vec2D { x: 10., y: 10. }, ZERO, 0., 1.),
+        5.,
+    ));
 
     group.bench_with_input(
         "circle to circle (not colliding)",
@@ -73,15 +47,7 @@ fn circle_to_circle_not_colliding_benchmark<M: Measurement>(group: &mut Benchmar
 }
 
 fn line_to_circle_colliding_benchmark<M: Measurement>(group: &mut BenchmarkGroup<'_, M>) {
-    let circle = DynamicBody::Circle(Circle {
-        body: BaseDynamicBody {
-            position: ZERO,
-            velocity: ZERO,
-            coefficient_of_restitution: 0.,
-            inverse_mass: 1.,
-        },
-        radius: 5.,
-    });
+    let circle = DynamicBody::Circle(Circle::new(BaseDynamicBody::new(ZERO, ZERO, 0., 1.), 5.));
 
     let line = StaticBody::Line(Line {
         normal: UNIT_RIGHT,
@@ -91,20 +57,12 @@ fn line_to_circle_colliding_benchmark<M: Measurement>(group: &mut BenchmarkGroup
     group.bench_with_input(
         "line to circle (colliding)",
         &(line, circle),
-        |b, (line, circle)| b.iter(|| generate_contact_static(line, circle)),
+        |b, (line, circle)| b.iter(|| generate_contact_static(line, 0, circle)),
     );
 }
 
 fn line_to_circle_not_colliding_benchmark<M: Measurement>(group: &mut BenchmarkGroup<'_, M>) {
-    let circle = DynamicBody::Circle(Circle {
-        body: BaseDynamicBody {
-            position: ZERO,
-            velocity: ZERO,
-            coefficient_of_restitution: 0.,
-            inverse_mass: 1.,
-        },
-        radius: 5.,
-    });
+    let circle = DynamicBody::Circle(Circle::new(BaseDynamicBody::new(ZERO, ZERO, 0., 1.), 5.));
 
     let line = StaticBody::Line(Line {
         normal: UNIT_RIGHT,
@@ -114,7 +72,7 @@ fn line_to_circle_not_colliding_benchmark<M: Measurement>(group: &mut BenchmarkG
     group.bench_with_input(
         "line to circle (not colliding)",
         &(line, circle),
-        |b, (line, circle)| b.iter(|| generate_contact_static(line, circle)),
+        |b, (line, circle)| b.iter(|| generate_contact_static(line, 0, circle)),
     );
 }
 
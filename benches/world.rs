@@ -5,7 +5,12 @@ fn world_tick_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("World");
 
     for num_bodies in [1, 10, 100, 1000, 10000] {
-        let world = World::generate(1920., 1080., 10., num_bodies, ZERO);
+        // About `num_bodies` circles and as many rectangles, as in earlier runs.
+        let world = World::generate(&GenerationSpec {
+            num_bodies: 2 * num_bodies,
+            gravity: ZERO,
+            ..GenerationSpec::default()
+        });
 
         group.bench_function(BenchmarkId::new("tick", num_bodies), |b| {
             b.iter(|| world.clone().tick(1.))
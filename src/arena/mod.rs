@@ -0,0 +1,307 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
+
+/// A reference to a value stored in an [`Arena`].
+///
+/// Each slot of the arena has a generation, which is bumped when its value is removed, so a
+/// handle to a removed value never refers to whatever takes its place.
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    fn new(index: u32, generation: u32) -> Self {
+        Self {
+            index,
+            generation,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+// Implemented by hand, as deriving would require `T` to implement these traits too.
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> PartialOrd for Handle<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Handle<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.index, self.generation).cmp(&(other.index, other.generation))
+    }
+}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Handle({}v{})", self.index, self.generation)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Entry<T> {
+    Occupied {
+        generation: u32,
+        value: T,
+    },
+    Vacant {
+        generation: u32,
+        next_free: Option<u32>,
+    },
+}
+
+/// A collection that hands out a stable [`Handle`] for each inserted value, and reuses the slots
+/// of removed values.
+#[derive(Debug, Clone)]
+pub struct Arena<T> {
+    entries: Vec<Entry<T>>,
+    free_head: Option<u32>,
+    len: usize,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+            free_head: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, value: T) -> Handle<T> {
+        self.len += 1;
+
+        match self.free_head {
+            Some(index) => {
+                let entry = &mut self.entries[index as usize];
+
+                let Entry::Vacant {
+                    generation,
+                    next_free,
+                } = *entry
+                else {
+                    unreachable!("the free list only contains vacant entries");
+                };
+
+                self.free_head = next_free;
+                *entry = Entry::Occupied { generation, value };
+
+                Handle::new(index, generation)
+            }
+            None => {
+                let index = self.entries.len() as u32;
+                self.entries.push(Entry::Occupied {
+                    generation: 0,
+                    value,
+                });
+
+                Handle::new(index, 0)
+            }
+        }
+    }
+
+    pub fn remove(&mut self, handle: Handle<T>) -> Option<T> {
+        if !self.contains(handle) {
+            return None;
+        }
+
+        let vacant = Entry::Vacant {
+            generation: handle.generation.wrapping_add(1),
+            next_free: self.free_head,
+        };
+
+        let Entry::Occupied { value, .. } =
+            std::mem::replace(&mut self.entries[handle.index()], vacant)
+        else {
+            unreachable!();
+        };
+
+        self.free_head = Some(handle.index);
+        self.len -= 1;
+
+        Some(value)
+    }
+
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.get(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        match self.entries.get(handle.index())? {
+            Entry::Occupied { generation, value } if *generation == handle.generation => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        match self.entries.get_mut(handle.index())? {
+            Entry::Occupied { generation, value } if *generation == handle.generation => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| match entry {
+                Entry::Occupied { generation, value } => {
+                    Some((Handle::new(index as u32, *generation), value))
+                }
+                Entry::Vacant { .. } => None,
+            })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Handle<T>, &mut T)> {
+        self.entries
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, entry)| match entry {
+                Entry::Occupied { generation, value } => {
+                    Some((Handle::new(index as u32, *generation), value))
+                }
+                Entry::Vacant { .. } => None,
+            })
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.iter_mut().map(|(_, value)| value)
+    }
+}
+
+impl<T> Index<Handle<T>> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, handle: Handle<T>) -> &T {
+        self.get(handle).expect("handle refers to a removed value")
+    }
+}
+
+impl<T> IndexMut<Handle<T>> for Arena<T> {
+    fn index_mut(&mut self, handle: Handle<T>) -> &mut T {
+        self.get_mut(handle)
+            .expect("handle refers to a removed value")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_get() {
+        let mut arena = Arena::new();
+
+        let a = arena.insert("a");
+        let b = arena.insert("b");
+
+        assert_eq!(arena.len(), 2);
+        assert_eq!(arena.get(a), Some(&"a"));
+        assert_eq!(arena[b], "b");
+    }
+
+    #[test]
+    fn test_removed_handles_are_invalidated() {
+        let mut arena = Arena::new();
+
+        let a = arena.insert(1);
+        let b = arena.insert(2);
+
+        assert_eq!(arena.remove(a), Some(1));
+        assert_eq!(arena.remove(a), None);
+        assert_eq!(arena.get(a), None);
+        assert_eq!(arena.get(b), Some(&2));
+        assert_eq!(arena.len(), 1);
+    }
+
+    #[test]
+    fn test_reused_slots_get_a_new_generation() {
+        let mut arena = Arena::new();
+
+        let a = arena.insert(1);
+        arena.remove(a);
+        let c = arena.insert(3);
+
+        assert_eq!(a.index(), c.index());
+        assert_ne!(a, c);
+        assert_eq!(arena.get(a), None);
+        assert_eq!(arena.get(c), Some(&3));
+    }
+
+    #[test]
+    fn test_iter_skips_removed_values() {
+        let mut arena = Arena::new();
+
+        let handles: Vec<_> = (0..5).map(|i| arena.insert(i)).collect();
+        arena.remove(handles[1]);
+        arena.remove(handles[3]);
+
+        let remaining: Vec<_> = arena.iter().collect();
+
+        assert_eq!(
+            remaining,
+            vec![(handles[0], &0), (handles[2], &2), (handles[4], &4)]
+        );
+    }
+}
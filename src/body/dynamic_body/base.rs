@@ -1,15 +1,89 @@
-use crate::vec2::Vec2D;
+use crate::vec2::{Vec2D, ZERO};
+
+pub const DEFAULT_STATIC_FRICTION: f64 = 0.6;
+pub const DEFAULT_DYNAMIC_FRICTION: f64 = 0.4;
 
 #[derive(Debug, Clone, Copy)]
 pub struct BaseDynamicBody {
     pub position: Vec2D,
     pub velocity: Vec2D,
+    pub orientation: f64,
+    pub angular_velocity: f64,
     pub coefficient_of_restitution: f64,
+    pub static_friction: f64,
+    pub dynamic_friction: f64,
     pub inverse_mass: f64,
+    pub inverse_moment_of_inertia: f64,
+    /// Bullets always get continuous collision detection, against dynamic bodies as well as
+    /// static ones, so that they cannot tunnel through anything however fast they move.
+    pub is_bullet: bool,
+    /// Sensors detect the bodies they overlap, reporting them as
+    /// [`SensorEvent`](crate::simulation::world::SensorEvent)s, but neither push nor are pushed
+    /// by them.
+    pub is_sensor: bool,
+    /// The force accumulated since the last tick, which turns into velocity and is cleared on the
+    /// next one.
+    pub force: Vec2D,
+    pub torque: f64,
 }
 
 impl BaseDynamicBody {
+    /// Creates a body with no rotation and the default friction coefficients. The moment of
+    /// inertia depends on the shape, so it is left at zero and filled in by the shape
+    /// constructors (e.g. [`super::Circle::new`]).
+    pub fn new(
+        position: Vec2D,
+        velocity: Vec2D,
+        coefficient_of_restitution: f64,
+        inverse_mass: f64,
+    ) -> Self {
+        Self {
+            position,
+            velocity,
+            orientation: 0.,
+            angular_velocity: 0.,
+            coefficient_of_restitution,
+            static_friction: DEFAULT_STATIC_FRICTION,
+            dynamic_friction: DEFAULT_DYNAMIC_FRICTION,
+            inverse_mass,
+            inverse_moment_of_inertia: 0.,
+            is_bullet: false,
+            is_sensor: false,
+            force: ZERO,
+            torque: 0.,
+        }
+    }
+
+    /// Changes the velocity by the accumulated force and torque over `elapsed`, then clears them.
+    pub fn integrate_forces(&mut self, elapsed: f64) {
+        self.velocity += &(&self.force * (self.inverse_mass * elapsed));
+        self.angular_velocity += self.torque * self.inverse_moment_of_inertia * elapsed;
+        self.force = ZERO;
+        self.torque = 0.;
+    }
+
     pub fn integrate(&mut self, elapsed: f64) {
         self.position += &(&self.velocity * elapsed);
+        self.orientation += self.angular_velocity * elapsed;
+    }
+
+    /// The velocity of the point at `contact_vector` from the centre of mass.
+    pub fn velocity_at(&self, contact_vector: &Vec2D) -> Vec2D {
+        &self.velocity + &Vec2D::scalar_cross_product(self.angular_velocity, contact_vector)
+    }
+
+    /// Applies `impulse` at `contact_vector` from the centre of mass, changing both the linear
+    /// and angular velocity.
+    pub fn apply_impulse(&mut self, impulse: &Vec2D, contact_vector: &Vec2D) {
+        self.velocity += &(impulse * self.inverse_mass);
+        self.angular_velocity +=
+            contact_vector.cross_product(impulse) * self.inverse_moment_of_inertia;
+    }
+
+    /// Adds `force`, acting at `contact_vector` from the centre of mass, to the accumulated force
+    /// and torque.
+    pub fn apply_force(&mut self, force: &Vec2D, contact_vector: &Vec2D) {
+        self.force += force;
+        self.torque += contact_vector.cross_product(force);
     }
 }
@@ -0,0 +1,95 @@
+use std::f64::consts::PI;
+
+use crate::{bounding_volume::BoundingVolume, vec2::Vec2D};
+
+use super::BaseDynamicBody;
+
+/// A segment of length `2 * half_length` along the body's local x axis, inflated by `radius`.
+#[derive(Debug, Clone, Copy)]
+pub struct Capsule {
+    pub body: BaseDynamicBody,
+    half_length: f64,
+    pub radius: f64,
+}
+
+impl Capsule {
+    /// Returns `None` if `half_length` is not positive, in which case a [`Circle`](super::Circle)
+    /// should be used instead.
+    pub fn new(mut body: BaseDynamicBody, half_length: f64, radius: f64) -> Option<Self> {
+        if half_length.is_nan() || half_length <= 0. {
+            return None;
+        }
+
+        // Split the mass between the central rectangle and the two half circles by area.
+        let rectangle_area = 4. * half_length * radius;
+        let circle_area = PI * radius * radius;
+
+        let mass_per_area = 1. / (body.inverse_mass * (rectangle_area + circle_area));
+
+        let rectangle_mass = rectangle_area * mass_per_area;
+        let circle_mass = circle_area * mass_per_area;
+
+        // Distance from the centre of each half circle to its centre of mass.
+        let half_circle_centroid = 4. * radius / (3. * PI);
+
+        let rectangle_inertia = rectangle_mass * (half_length * half_length + radius * radius) / 3.;
+        let circle_inertia = circle_mass
+            * (radius * radius / 2.
+                + half_length * half_length
+                + 2. * half_length * half_circle_centroid);
+
+        body.inverse_moment_of_inertia = 1. / (rectangle_inertia + circle_inertia);
+
+        Some(Self {
+            body,
+            half_length,
+            radius,
+        })
+    }
+
+    pub fn half_length(&self) -> f64 {
+        self.half_length
+    }
+
+    /// The ends of the capsule's central segment, in world space.
+    pub fn endpoints(&self) -> [Vec2D; 2] {
+        let (sin, cos) = self.body.orientation.sin_cos();
+
+        let half_segment = Vec2D {
+            x: cos * self.half_length,
+            y: sin * self.half_length,
+        };
+
+        [
+            &self.body.position - &half_segment,
+            &self.body.position + &half_segment,
+        ]
+    }
+
+    #[inline]
+    pub fn to_bounding_volume(&self) -> BoundingVolume {
+        let [start, end] = self.endpoints();
+
+        let extents = Vec2D {
+            x: self.radius,
+            y: self.radius,
+        };
+
+        BoundingVolume {
+            top_left: &start.min(&end) - &extents,
+            bottom_right: &start.max(&end) + &extents,
+        }
+    }
+}
+
+impl AsRef<BaseDynamicBody> for Capsule {
+    fn as_ref(&self) -> &BaseDynamicBody {
+        &self.body
+    }
+}
+
+impl AsMut<BaseDynamicBody> for Capsule {
+    fn as_mut(&mut self) -> &mut BaseDynamicBody {
+        &mut self.body
+    }
+}
@@ -9,6 +9,13 @@ pub struct Circle {
 }
 
 impl Circle {
+    pub fn new(mut body: BaseDynamicBody, radius: f64) -> Self {
+        // I = m * r^2 / 2
+        body.inverse_moment_of_inertia = 2. * body.inverse_mass / (radius * radius);
+
+        Self { body, radius }
+    }
+
     #[inline]
     pub fn to_bounding_volume(&self) -> BoundingVolume {
         let extents = Vec2D {
@@ -1,9 +1,13 @@
 mod base;
+mod capsule;
 mod circle;
+mod polygon;
 mod rectangle;
 
 pub use base::*;
+pub use capsule::*;
 pub use circle::*;
+pub use polygon::*;
 pub use rectangle::*;
 
 use crate::bounding_volume::BoundingVolume;
@@ -12,6 +16,8 @@ use crate::bounding_volume::BoundingVolume;
 pub enum DynamicBody {
     Circle(Circle),
     Rectangle(Rectangle),
+    Polygon(Polygon),
+    Capsule(Capsule),
 }
 
 impl DynamicBody {
@@ -20,6 +26,23 @@ impl DynamicBody {
         match self {
             DynamicBody::Circle(circle) => circle.to_bounding_volume(),
             DynamicBody::Rectangle(rectangle) => rectangle.to_bounding_volume(),
+            DynamicBody::Polygon(polygon) => polygon.to_bounding_volume(),
+            DynamicBody::Capsule(capsule) => capsule.to_bounding_volume(),
+        }
+    }
+
+    /// The radius of the largest circle around the body's position that fits inside it.
+    pub fn inner_radius(&self) -> f64 {
+        match self {
+            DynamicBody::Circle(circle) => circle.radius,
+            DynamicBody::Rectangle(rectangle) => rectangle.half_width.min(rectangle.half_height),
+            DynamicBody::Polygon(polygon) => polygon
+                .local_vertices()
+                .iter()
+                .zip(polygon.local_normals())
+                .map(|(vertex, normal)| vertex.dot_product(&normal))
+                .fold(f64::INFINITY, f64::min),
+            DynamicBody::Capsule(capsule) => capsule.radius,
         }
     }
 }
@@ -29,6 +52,8 @@ impl AsRef<BaseDynamicBody> for DynamicBody {
         match self {
             Self::Circle(circle) => circle.as_ref(),
             Self::Rectangle(rectangle) => rectangle.as_ref(),
+            Self::Polygon(polygon) => polygon.as_ref(),
+            Self::Capsule(capsule) => capsule.as_ref(),
         }
     }
 }
@@ -38,6 +63,8 @@ impl AsMut<BaseDynamicBody> for DynamicBody {
         match self {
             Self::Circle(circle) => circle.as_mut(),
             Self::Rectangle(rectangle) => rectangle.as_mut(),
+            Self::Polygon(polygon) => polygon.as_mut(),
+            Self::Capsule(capsule) => capsule.as_mut(),
         }
     }
 }
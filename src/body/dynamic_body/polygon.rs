@@ -0,0 +1,282 @@
+use crate::{
+    bounding_volume::BoundingVolume,
+    vec2::{Vec2D, ZERO},
+};
+
+use super::BaseDynamicBody;
+
+pub const MAX_POLYGON_VERTICES: usize = 8;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Polygon {
+    pub body: BaseDynamicBody,
+    vertices: [Vec2D; MAX_POLYGON_VERTICES],
+    vertex_count: usize,
+    area: f64,
+}
+
+impl Polygon {
+    /// Creates a convex polygon from `vertices`, given relative to `body.position` in the body's
+    /// frame of reference. The body is moved so that its position is the polygon's centroid, and
+    /// its mass is spread evenly over the polygon's area.
+    ///
+    /// Returns `None` if there are fewer than 3 or more than [`MAX_POLYGON_VERTICES`] vertices, or
+    /// if they do not form a convex polygon.
+    pub fn new(mut body: BaseDynamicBody, vertices: &[Vec2D]) -> Option<Self> {
+        if vertices.len() < 3 || vertices.len() > MAX_POLYGON_VERTICES {
+            return None;
+        }
+
+        let mut local_vertices = [ZERO; MAX_POLYGON_VERTICES];
+        local_vertices[..vertices.len()].copy_from_slice(vertices);
+        let local_vertices = &mut local_vertices[..vertices.len()];
+
+        let (area, centroid) = area_and_centroid(local_vertices);
+
+        if area == 0. {
+            return None;
+        }
+
+        // Use a consistent winding so that the edge normals point outwards.
+        if area < 0. {
+            local_vertices.reverse();
+        }
+
+        for vertex in local_vertices.iter_mut() {
+            *vertex -= &centroid;
+        }
+
+        for (i, vertex) in local_vertices.iter().enumerate() {
+            let next_vertex = &local_vertices[(i + 1) % local_vertices.len()];
+
+            let edge = next_vertex - vertex;
+            let next_edge = &local_vertices[(i + 2) % local_vertices.len()] - next_vertex;
+
+            if edge.cross_product(&next_edge) <= 0. {
+                return None;
+            }
+        }
+
+        body.position += &centroid.rotate(body.orientation);
+        body.inverse_moment_of_inertia =
+            body.inverse_mass * area.abs() / unit_density_moment_of_inertia(local_vertices);
+
+        let mut polygon = Self {
+            body,
+            vertices: [ZERO; MAX_POLYGON_VERTICES],
+            vertex_count: local_vertices.len(),
+            area: area.abs(),
+        };
+
+        polygon.vertices[..local_vertices.len()].copy_from_slice(local_vertices);
+
+        Some(polygon)
+    }
+
+    /// Like [`Polygon::new`], but with the mass derived from the polygon's area.
+    pub fn with_density(body: BaseDynamicBody, vertices: &[Vec2D], density: f64) -> Option<Self> {
+        let mut polygon = Self::new(body, vertices)?;
+
+        polygon.body.inverse_mass = 1. / (density * polygon.area);
+        polygon.body.inverse_moment_of_inertia =
+            1. / (density * unit_density_moment_of_inertia(polygon.local_vertices()));
+
+        Some(polygon)
+    }
+
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+
+    /// The vertices relative to the centroid, in the body's frame of reference.
+    pub fn local_vertices(&self) -> &[Vec2D] {
+        &self.vertices[..self.vertex_count]
+    }
+
+    /// The outward normal of each edge (from vertex `i` to vertex `i + 1`), in the body's frame
+    /// of reference.
+    pub fn local_normals(&self) -> impl Iterator<Item = Vec2D> + '_ {
+        let vertices = self.local_vertices();
+
+        vertices.iter().enumerate().map(|(i, vertex)| {
+            let edge = &vertices[(i + 1) % vertices.len()] - vertex;
+
+            &Vec2D {
+                x: edge.y,
+                y: -edge.x,
+            } / edge.length()
+        })
+    }
+
+    pub fn world_vertices(&self) -> impl Iterator<Item = Vec2D> + '_ {
+        self.local_vertices()
+            .iter()
+            .map(|vertex| &self.body.position + &vertex.rotate(self.body.orientation))
+    }
+
+    #[inline]
+    pub fn to_bounding_volume(&self) -> BoundingVolume {
+        let mut vertices = self.world_vertices();
+
+        // Safe because there are always at least 3 vertices
+        let first = vertices.next().unwrap();
+
+        vertices.fold(
+            BoundingVolume {
+                top_left: first,
+                bottom_right: first,
+            },
+            |bounding_volume, vertex| BoundingVolume {
+                top_left: bounding_volume.top_left.min(&vertex),
+                bottom_right: bounding_volume.bottom_right.max(&vertex),
+            },
+        )
+    }
+}
+
+/// The signed area (positive if the vertices wind clockwise on screen) and centroid.
+fn area_and_centroid(vertices: &[Vec2D]) -> (f64, Vec2D) {
+    let mut area = 0.;
+    let mut centroid = ZERO;
+
+    // Triangulate from the first vertex to reduce rounding errors.
+    let origin = vertices[0];
+
+    for i in 1..vertices.len() - 1 {
+        let e1 = &vertices[i] - &origin;
+        let e2 = &vertices[i + 1] - &origin;
+
+        let triangle_area = e1.cross_product(&e2) / 2.;
+
+        area += triangle_area;
+        centroid += &(&(&e1 + &e2) * (triangle_area / 3.));
+    }
+
+    if area == 0. {
+        return (0., origin);
+    }
+
+    (area, &origin + &(&centroid / area))
+}
+
+/// The moment of inertia around the origin, assuming a density of 1.
+fn unit_density_moment_of_inertia(vertices: &[Vec2D]) -> f64 {
+    let mut moment_of_inertia = 0.;
+
+    for (i, e1) in vertices.iter().enumerate() {
+        let e2 = &vertices[(i + 1) % vertices.len()];
+
+        let cross_product = e1.cross_product(e2);
+
+        let x_squared = e1.x * e1.x + e1.x * e2.x + e2.x * e2.x;
+        let y_squared = e1.y * e1.y + e1.y * e2.y + e2.y * e2.y;
+
+        moment_of_inertia += cross_product * (x_squared + y_squared) / 12.;
+    }
+
+    moment_of_inertia
+}
+
+impl AsRef<BaseDynamicBody> for Polygon {
+    fn as_ref(&self) -> &BaseDynamicBody {
+        &self.body
+    }
+}
+
+impl AsMut<BaseDynamicBody> for Polygon {
+    fn as_mut(&mut self) -> &mut BaseDynamicBody {
+        &mut self.body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::Rectangle;
+
+    fn square_vertices(half_extent: f64) -> [Vec2D; 4] {
+        [
+            Vec2D {
+                x: -half_extent,
+                y: -half_extent,
+            },
+            Vec2D {
+                x: half_extent,
+                y: -half_extent,
+            },
+            Vec2D {
+                x: half_extent,
+                y: half_extent,
+            },
+            Vec2D {
+                x: -half_extent,
+                y: half_extent,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_new_moves_the_body_to_the_centroid() {
+        let body = BaseDynamicBody::new(ZERO, ZERO, 1., 1.);
+        let triangle = [ZERO, Vec2D { x: 3., y: 0. }, Vec2D { x: 0., y: 3. }];
+
+        let polygon = Polygon::new(body, &triangle).unwrap();
+
+        assert_eq!(polygon.body.position, Vec2D { x: 1., y: 1. });
+        assert_eq!(polygon.area(), 4.5);
+    }
+
+    #[test]
+    fn test_new_matches_rectangle_moment_of_inertia() {
+        let body = BaseDynamicBody::new(ZERO, ZERO, 1., 0.5);
+
+        let polygon = Polygon::new(body, &square_vertices(2.)).unwrap();
+        let rectangle = Rectangle::new(body, 2., 2.);
+
+        assert!(
+            (polygon.body.inverse_moment_of_inertia - rectangle.body.inverse_moment_of_inertia)
+                .abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_new_accepts_either_winding() {
+        let body = BaseDynamicBody::new(ZERO, ZERO, 1., 1.);
+
+        let mut vertices = square_vertices(1.);
+        vertices.reverse();
+
+        let polygon = Polygon::new(body, &vertices).unwrap();
+
+        assert!(
+            polygon
+                .local_normals()
+                .zip(polygon.local_vertices())
+                .all(|(normal, vertex)| normal.dot_product(vertex) > 0.)
+        );
+    }
+
+    #[test]
+    fn test_new_rejects_concave_polygons() {
+        let body = BaseDynamicBody::new(ZERO, ZERO, 1., 1.);
+        let arrow = [
+            ZERO,
+            Vec2D { x: 2., y: 2. },
+            Vec2D { x: 0., y: 1. },
+            Vec2D { x: -2., y: 2. },
+        ];
+
+        assert!(Polygon::new(body, &arrow).is_none());
+        assert!(Polygon::new(body, &arrow[..2]).is_none());
+    }
+
+    #[test]
+    fn test_with_density_uses_the_area() {
+        let body = BaseDynamicBody::new(ZERO, ZERO, 1., 1.);
+
+        let polygon = Polygon::with_density(body, &square_vertices(1.), 2.).unwrap();
+
+        assert_eq!(polygon.body.inverse_mass, 1. / 8.);
+    }
+}
@@ -10,11 +10,60 @@ pub struct Rectangle {
 }
 
 impl Rectangle {
+    pub fn new(mut body: BaseDynamicBody, half_width: f64, half_height: f64) -> Self {
+        // I = m * (w^2 + h^2) / 12, with w = 2 * half_width and h = 2 * half_height
+        body.inverse_moment_of_inertia =
+            3. * body.inverse_mass / (half_width * half_width + half_height * half_height);
+
+        Self {
+            body,
+            half_width,
+            half_height,
+        }
+    }
+
+    /// The rectangle's local x and y axes, in world space.
+    #[inline]
+    pub fn axes(&self) -> [Vec2D; 2] {
+        let (sin, cos) = self.body.orientation.sin_cos();
+
+        [Vec2D { x: cos, y: sin }, Vec2D { x: -sin, y: cos }]
+    }
+
+    /// The corners of the rectangle in world space, in clockwise order on screen.
+    pub fn corners(&self) -> [Vec2D; 4] {
+        let [x_axis, y_axis] = self.axes();
+
+        let half_x = &x_axis * self.half_width;
+        let half_y = &y_axis * self.half_height;
+
+        let position = &self.body.position;
+
+        [
+            &(position - &half_x) - &half_y,
+            &(position + &half_x) - &half_y,
+            &(position + &half_x) + &half_y,
+            &(position - &half_x) + &half_y,
+        ]
+    }
+
+    /// Half the length of the rectangle's projection onto `axis`, which must be normalised.
+    #[inline]
+    pub fn projected_radius(&self, axis: &Vec2D) -> f64 {
+        let [x_axis, y_axis] = self.axes();
+
+        self.half_width * x_axis.dot_product(axis).abs()
+            + self.half_height * y_axis.dot_product(axis).abs()
+    }
+
     #[inline]
     pub fn to_bounding_volume(&self) -> BoundingVolume {
+        let (sin, cos) = self.body.orientation.sin_cos();
+        let (sin, cos) = (sin.abs(), cos.abs());
+
         let extents = Vec2D {
-            x: self.half_width,
-            y: self.half_height,
+            x: cos * self.half_width + sin * self.half_height,
+            y: sin * self.half_width + cos * self.half_height,
         };
 
         BoundingVolume {
@@ -1,8 +1,57 @@
 mod line;
+mod segment;
 
 pub use line::*;
+pub use segment::*;
 
-#[derive(Debug, Clone, Copy)]
+use super::{BaseDynamicBody, Circle, Polygon, Rectangle};
+use crate::bounding_volume::BoundingVolume;
+
+/// A body that never moves.
+///
+/// The solid shapes are the same as the dynamic ones, and collide in the same way, but with an
+/// infinite mass. Only the position, orientation and material (the coefficient of restitution
+/// and friction) of their base body are used, so it can be created with
+/// `BaseDynamicBody::new(position, ZERO, coefficient_of_restitution, 0.)`.
+#[derive(Debug, Clone)]
 pub enum StaticBody {
     Line(Line),
+    Segment(Segment),
+    Chain(Chain),
+    Circle(Circle),
+    Rectangle(Rectangle),
+    Polygon(Polygon),
+}
+
+impl StaticBody {
+    /// How many parts the body has: the segments of a chain, or just the body itself.
+    pub fn part_count(&self) -> usize {
+        match self {
+            StaticBody::Chain(chain) => chain.segment_count(),
+            _ => 1,
+        }
+    }
+
+    /// The body whose restitution and friction are combined with those of the bodies touching
+    /// it, or `None` if it has no material of its own and theirs is used as it is.
+    pub fn material(&self) -> Option<&BaseDynamicBody> {
+        match self {
+            StaticBody::Line(_) | StaticBody::Segment(_) | StaticBody::Chain(_) => None,
+            StaticBody::Circle(circle) => Some(circle.as_ref()),
+            StaticBody::Rectangle(rectangle) => Some(rectangle.as_ref()),
+            StaticBody::Polygon(polygon) => Some(polygon.as_ref()),
+        }
+    }
+
+    /// The bounding volume of one of the body's parts, or `None` if it is infinite.
+    pub fn part_bounding_volume(&self, part: usize) -> Option<BoundingVolume> {
+        match self {
+            StaticBody::Line(_) => None,
+            StaticBody::Segment(segment) => Some(segment.to_bounding_volume()),
+            StaticBody::Chain(chain) => Some(chain.segment(part).to_bounding_volume()),
+            StaticBody::Circle(circle) => Some(circle.to_bounding_volume()),
+            StaticBody::Rectangle(rectangle) => Some(rectangle.to_bounding_volume()),
+            StaticBody::Polygon(polygon) => Some(polygon.to_bounding_volume()),
+        }
+    }
 }
@@ -0,0 +1,109 @@
+use crate::{bounding_volume::BoundingVolume, vec2::Vec2D};
+
+/// A finite line segment.
+///
+/// A one-sided segment only collides with bodies in front of it, i.e. on the side of
+/// [`Segment::normal`], which is on the left when going from `start` to `end` on screen.
+///
+/// The ghost vertices are the neighbouring vertices in a [`Chain`](super::Chain). They are used
+/// to stop bodies sliding along the chain from catching on the vertices between segments.
+#[derive(Debug, Clone, Copy)]
+pub struct Segment {
+    start: Vec2D,
+    end: Vec2D,
+    pub two_sided: bool,
+    pub ghost_start: Option<Vec2D>,
+    pub ghost_end: Option<Vec2D>,
+}
+
+impl Segment {
+    /// Returns `None` if `start` and `end` are the same.
+    pub fn new(start: Vec2D, end: Vec2D, two_sided: bool) -> Option<Self> {
+        if start == end {
+            return None;
+        }
+
+        Some(Self {
+            start,
+            end,
+            two_sided,
+            ghost_start: None,
+            ghost_end: None,
+        })
+    }
+
+    pub fn start(&self) -> Vec2D {
+        self.start
+    }
+
+    pub fn end(&self) -> Vec2D {
+        self.end
+    }
+
+    pub fn direction(&self) -> Vec2D {
+        let direction = &self.end - &self.start;
+
+        &direction / direction.length()
+    }
+
+    pub fn normal(&self) -> Vec2D {
+        let direction = self.direction();
+
+        Vec2D {
+            x: direction.y,
+            y: -direction.x,
+        }
+    }
+
+    pub fn to_bounding_volume(&self) -> BoundingVolume {
+        BoundingVolume {
+            top_left: self.start.min(&self.end),
+            bottom_right: self.start.max(&self.end),
+        }
+    }
+}
+
+/// A polyline made of connected segments.
+#[derive(Debug, Clone)]
+pub struct Chain {
+    vertices: Vec<Vec2D>,
+    pub two_sided: bool,
+}
+
+impl Chain {
+    /// Returns `None` if there are fewer than 2 vertices, or if two consecutive vertices are the
+    /// same.
+    pub fn new(vertices: Vec<Vec2D>, two_sided: bool) -> Option<Self> {
+        if vertices.len() < 2 || vertices.windows(2).any(|pair| pair[0] == pair[1]) {
+            return None;
+        }
+
+        Some(Self {
+            vertices,
+            two_sided,
+        })
+    }
+
+    pub fn vertices(&self) -> &[Vec2D] {
+        &self.vertices
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.vertices.len() - 1
+    }
+
+    /// Segment `i` of the chain, with its neighbouring vertices as ghost vertices.
+    pub fn segment(&self, i: usize) -> Segment {
+        Segment {
+            start: self.vertices[i],
+            end: self.vertices[i + 1],
+            two_sided: self.two_sided,
+            ghost_start: i.checked_sub(1).map(|j| self.vertices[j]),
+            ghost_end: self.vertices.get(i + 2).copied(),
+        }
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        (0..self.segment_count()).map(|i| self.segment(i))
+    }
+}
@@ -0,0 +1,555 @@
+use crate::vec2::Vec2D;
+
+use super::BoundingVolume;
+
+/// How much the bounding volumes in a [`DynamicTree`] are fattened on every side, so that bodies
+/// can move a little without having to be reinserted.
+pub const FAT_BOUNDING_VOLUME_MARGIN: f64 = 2.;
+
+/// How many times its displacement a moving body's fat bounding volume is stretched in the
+/// direction of motion.
+pub const FAT_BOUNDING_VOLUME_DISPLACEMENT_MULTIPLIER: f64 = 4.;
+
+pub type ProxyId = usize;
+
+const NULL_NODE: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    bounding_volume: BoundingVolume,
+    parent: usize,
+    children: [usize; 2],
+    /// The height of the subtree, 0 for a leaf.
+    height: usize,
+    /// Only set for leaves.
+    data: Option<T>,
+}
+
+impl<T> Node<T> {
+    fn is_leaf(&self) -> bool {
+        self.children[0] == NULL_NODE
+    }
+}
+
+/// A bounding volume hierarchy which is updated incrementally, rather than rebuilt, as the
+/// bodies move.
+///
+/// Each leaf (or proxy) stores a fat bounding volume, which only needs updating when the body
+/// leaves it. The tree is kept balanced with rotations, like an AVL tree.
+#[derive(Debug, Clone)]
+pub struct DynamicTree<T> {
+    nodes: Vec<Node<T>>,
+    root: usize,
+    free_nodes: Vec<usize>,
+}
+
+impl<T: Copy> Default for DynamicTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy> DynamicTree<T> {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![],
+            root: NULL_NODE,
+            free_nodes: vec![],
+        }
+    }
+
+    /// Inserts a proxy for a body with the given (tight) bounding volume.
+    pub fn insert(&mut self, bounding_volume: &BoundingVolume, data: T) -> ProxyId {
+        let leaf = self.allocate_node(bounding_volume.expand(FAT_BOUNDING_VOLUME_MARGIN));
+        self.nodes[leaf].data = Some(data);
+
+        self.insert_leaf(leaf);
+
+        leaf
+    }
+
+    pub fn remove(&mut self, proxy: ProxyId) {
+        self.remove_leaf(proxy);
+        self.free_node(proxy);
+    }
+
+    /// Updates the proxy of a body that now has the given bounding volume, and is expected to
+    /// move by `displacement` before the next update. The proxy is only reinserted, in which case
+    /// this returns `true`, if the body left its fat bounding volume.
+    pub fn move_proxy(
+        &mut self,
+        proxy: ProxyId,
+        bounding_volume: &BoundingVolume,
+        displacement: &Vec2D,
+    ) -> bool {
+        if self.nodes[proxy].bounding_volume.contains(bounding_volume) {
+            return false;
+        }
+
+        self.remove_leaf(proxy);
+
+        let mut fat_bounding_volume = bounding_volume.expand(FAT_BOUNDING_VOLUME_MARGIN);
+        let displacement = displacement * FAT_BOUNDING_VOLUME_DISPLACEMENT_MULTIPLIER;
+
+        if displacement.x < 0. {
+            fat_bounding_volume.top_left.x += displacement.x;
+        } else {
+            fat_bounding_volume.bottom_right.x += displacement.x;
+        }
+
+        if displacement.y < 0. {
+            fat_bounding_volume.top_left.y += displacement.y;
+        } else {
+            fat_bounding_volume.bottom_right.y += displacement.y;
+        }
+
+        self.nodes[proxy].bounding_volume = fat_bounding_volume;
+        self.insert_leaf(proxy);
+
+        true
+    }
+
+    pub fn fat_bounding_volume(&self, proxy: ProxyId) -> &BoundingVolume {
+        &self.nodes[proxy].bounding_volume
+    }
+
+    /// The height of the tree, 0 if it is empty or only has one leaf.
+    pub fn height(&self) -> usize {
+        self.nodes.get(self.root).map_or(0, |root| root.height)
+    }
+
+    /// Calls `callback` with the data of every proxy whose fat bounding volume intersects
+    /// `bounding_volume`.
+    pub fn query(&self, bounding_volume: &BoundingVolume, mut callback: impl FnMut(T)) {
+        if self.root != NULL_NODE {
+            self.query_node(self.root, bounding_volume, &mut callback);
+        }
+    }
+
+    /// Calls `callback` with the data of every proxy whose fat bounding volume intersects the
+    /// segment from `start` to `end`.
+    pub fn query_segment(&self, start: &Vec2D, end: &Vec2D, mut callback: impl FnMut(T)) {
+        if self.root != NULL_NODE {
+            self.query_segment_node(self.root, start, end, &mut callback);
+        }
+    }
+
+    /// Calls `callback` once for every pair of proxies whose fat bounding volumes intersect, by
+    /// traversing the tree against itself.
+    pub fn for_each_pair(&self, mut callback: impl FnMut(T, T)) {
+        if self.root != NULL_NODE {
+            self.self_pairs(self.root, &mut callback);
+        }
+    }
+
+    fn query_node(
+        &self,
+        index: usize,
+        bounding_volume: &BoundingVolume,
+        callback: &mut impl FnMut(T),
+    ) {
+        let node = &self.nodes[index];
+
+        if !node.bounding_volume.is_intersecting(bounding_volume) {
+            return;
+        }
+
+        match node.data {
+            Some(data) => callback(data),
+            None => {
+                for child in node.children {
+                    self.query_node(child, bounding_volume, callback);
+                }
+            }
+        }
+    }
+
+    fn query_segment_node(
+        &self,
+        index: usize,
+        start: &Vec2D,
+        end: &Vec2D,
+        callback: &mut impl FnMut(T),
+    ) {
+        let node = &self.nodes[index];
+
+        if !node.bounding_volume.is_intersecting_segment(start, end) {
+            return;
+        }
+
+        match node.data {
+            Some(data) => callback(data),
+            None => {
+                for child in node.children {
+                    self.query_segment_node(child, start, end, callback);
+                }
+            }
+        }
+    }
+
+    fn self_pairs(&self, index: usize, callback: &mut impl FnMut(T, T)) {
+        let node = &self.nodes[index];
+
+        if node.is_leaf() {
+            return;
+        }
+
+        let [left, right] = node.children;
+
+        self.cross_pairs(left, right, callback);
+        self.self_pairs(left, callback);
+        self.self_pairs(right, callback);
+    }
+
+    fn cross_pairs(&self, this: usize, that: usize, callback: &mut impl FnMut(T, T)) {
+        let this_node = &self.nodes[this];
+        let that_node = &self.nodes[that];
+
+        if !this_node
+            .bounding_volume
+            .is_intersecting(&that_node.bounding_volume)
+        {
+            return;
+        }
+
+        match (this_node.data, that_node.data) {
+            (Some(this_data), Some(that_data)) => callback(this_data, that_data),
+            // Descend into the larger node first.
+            (None, Some(_)) => self.cross_children(this, that, callback),
+            (Some(_), None) => self.cross_children(that, this, callback),
+            (None, None) => {
+                if this_node.bounding_volume.perimeter() > that_node.bounding_volume.perimeter() {
+                    self.cross_children(this, that, callback);
+                } else {
+                    self.cross_children(that, this, callback);
+                }
+            }
+        }
+    }
+
+    fn cross_children(&self, parent: usize, other: usize, callback: &mut impl FnMut(T, T)) {
+        for child in self.nodes[parent].children {
+            self.cross_pairs(child, other, callback);
+        }
+    }
+
+    fn allocate_node(&mut self, bounding_volume: BoundingVolume) -> usize {
+        let node = Node {
+            bounding_volume,
+            parent: NULL_NODE,
+            children: [NULL_NODE; 2],
+            height: 0,
+            data: None,
+        };
+
+        match self.free_nodes.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn free_node(&mut self, index: usize) {
+        self.nodes[index].data = None;
+        self.free_nodes.push(index);
+    }
+
+    fn insert_leaf(&mut self, leaf: usize) {
+        if self.root == NULL_NODE {
+            self.root = leaf;
+            self.nodes[leaf].parent = NULL_NODE;
+            return;
+        }
+
+        let sibling = self.find_best_sibling(&self.nodes[leaf].bounding_volume);
+
+        let old_parent = self.nodes[sibling].parent;
+        let new_parent = self.allocate_node(
+            self.nodes[leaf]
+                .bounding_volume
+                .union(&self.nodes[sibling].bounding_volume),
+        );
+
+        self.nodes[new_parent].parent = old_parent;
+        self.nodes[new_parent].children = [sibling, leaf];
+        self.nodes[new_parent].height = self.nodes[sibling].height + 1;
+        self.nodes[sibling].parent = new_parent;
+        self.nodes[leaf].parent = new_parent;
+
+        if old_parent == NULL_NODE {
+            self.root = new_parent;
+        } else {
+            self.replace_child(old_parent, sibling, new_parent);
+        }
+
+        self.refit_ancestors(self.nodes[leaf].parent);
+    }
+
+    /// Descends the tree towards the node that is cheapest to pair with a new leaf, using the
+    /// perimeters of the bounding volumes as the cost.
+    fn find_best_sibling(&self, bounding_volume: &BoundingVolume) -> usize {
+        let mut index = self.root;
+
+        while !self.nodes[index].is_leaf() {
+            let node = &self.nodes[index];
+
+            let perimeter = node.bounding_volume.perimeter();
+            let combined_perimeter = node.bounding_volume.union(bounding_volume).perimeter();
+
+            // The cost of making the leaf a sibling of this node.
+            let cost = 2. * combined_perimeter;
+
+            // The minimum cost of pushing the leaf further down the tree.
+            let inheritance_cost = 2. * (combined_perimeter - perimeter);
+
+            let child_cost = |child: usize| {
+                let child = &self.nodes[child];
+                let combined = child.bounding_volume.union(bounding_volume).perimeter();
+
+                if child.is_leaf() {
+                    combined + inheritance_cost
+                } else {
+                    combined - child.bounding_volume.perimeter() + inheritance_cost
+                }
+            };
+
+            let [left, right] = node.children;
+            let (left_cost, right_cost) = (child_cost(left), child_cost(right));
+
+            if cost < left_cost && cost < right_cost {
+                break;
+            }
+
+            index = if left_cost < right_cost { left } else { right };
+        }
+
+        index
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) {
+        if leaf == self.root {
+            self.root = NULL_NODE;
+            return;
+        }
+
+        let parent = self.nodes[leaf].parent;
+        let grand_parent = self.nodes[parent].parent;
+
+        let [left, right] = self.nodes[parent].children;
+        let sibling = if left == leaf { right } else { left };
+
+        self.nodes[sibling].parent = grand_parent;
+        self.free_node(parent);
+
+        if grand_parent == NULL_NODE {
+            self.root = sibling;
+        } else {
+            self.replace_child(grand_parent, parent, sibling);
+            self.refit_ancestors(grand_parent);
+        }
+    }
+
+    fn replace_child(&mut self, parent: usize, old_child: usize, new_child: usize) {
+        let children = &mut self.nodes[parent].children;
+
+        if children[0] == old_child {
+            children[0] = new_child;
+        } else {
+            children[1] = new_child;
+        }
+    }
+
+    /// Rebalances and updates the bounding volumes and heights from `index` up to the root.
+    fn refit_ancestors(&mut self, mut index: usize) {
+        while index != NULL_NODE {
+            index = self.balance(index);
+
+            self.refit(index);
+
+            index = self.nodes[index].parent;
+        }
+    }
+
+    fn refit(&mut self, index: usize) {
+        let [left, right] = self.nodes[index].children;
+
+        self.nodes[index].height = 1 + self.nodes[left].height.max(self.nodes[right].height);
+        self.nodes[index].bounding_volume = self.nodes[left]
+            .bounding_volume
+            .union(&self.nodes[right].bounding_volume);
+    }
+
+    /// If one child of `index` is more than one level taller than the other, rotates its taller
+    /// grandchild up. Returns the index of the node that now takes the place of `index`.
+    fn balance(&mut self, index: usize) -> usize {
+        let node = &self.nodes[index];
+
+        if node.is_leaf() || node.height < 2 {
+            return index;
+        }
+
+        let [left, right] = node.children;
+        let balance = self.nodes[right].height as isize - self.nodes[left].height as isize;
+
+        if balance > 1 {
+            self.rotate_up(index, 1)
+        } else if balance < -1 {
+            self.rotate_up(index, 0)
+        } else {
+            index
+        }
+    }
+
+    /// Moves child `side` of `index` up to take its place. `index` keeps its other child, and
+    /// adopts the shorter of the promoted node's children.
+    fn rotate_up(&mut self, index: usize, side: usize) -> usize {
+        let promoted = self.nodes[index].children[side];
+        let [first, second] = self.nodes[promoted].children;
+
+        let (taller, shorter) = if self.nodes[first].height > self.nodes[second].height {
+            (first, second)
+        } else {
+            (second, first)
+        };
+
+        let parent = self.nodes[index].parent;
+
+        self.nodes[promoted].parent = parent;
+        self.nodes[index].parent = promoted;
+
+        if parent == NULL_NODE {
+            self.root = promoted;
+        } else {
+            self.replace_child(parent, index, promoted);
+        }
+
+        self.nodes[promoted].children = [index, taller];
+        self.nodes[index].children[side] = shorter;
+        self.nodes[shorter].parent = index;
+
+        self.refit(index);
+        self.refit(promoted);
+
+        promoted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec2::ZERO;
+
+    use super::*;
+
+    fn create_square(top_left: Vec2D, width: f64) -> BoundingVolume {
+        BoundingVolume {
+            top_left,
+            bottom_right: Vec2D {
+                x: top_left.x + width,
+                y: top_left.y + width,
+            },
+        }
+    }
+
+    fn square_at(i: usize) -> BoundingVolume {
+        create_square(
+            Vec2D {
+                x: 20. * i as f64,
+                y: 0.,
+            },
+            10.,
+        )
+    }
+
+    impl<T: Copy> DynamicTree<T> {
+        /// Checks the parent links, heights and bounding volumes of the tree, and returns its
+        /// number of leaves.
+        fn validate(&self, index: usize) -> usize {
+            let node = &self.nodes[index];
+
+            if node.is_leaf() {
+                assert_eq!(node.height, 0);
+                return 1;
+            }
+
+            let [left, right] = node.children;
+
+            assert_eq!(self.nodes[left].parent, index);
+            assert_eq!(self.nodes[right].parent, index);
+            assert_eq!(
+                node.height,
+                1 + self.nodes[left].height.max(self.nodes[right].height)
+            );
+            assert!(self.nodes[left].height.abs_diff(self.nodes[right].height) <= 1);
+            assert!(
+                node.bounding_volume
+                    .contains(&self.nodes[left].bounding_volume)
+            );
+            assert!(
+                node.bounding_volume
+                    .contains(&self.nodes[right].bounding_volume)
+            );
+
+            self.validate(left) + self.validate(right)
+        }
+    }
+
+    #[test]
+    fn test_tree_stays_balanced() {
+        let mut tree = DynamicTree::new();
+
+        let proxies: Vec<_> = (0..100).map(|i| tree.insert(&square_at(i), i)).collect();
+
+        assert_eq!(tree.validate(tree.root), 100);
+        assert!(tree.height() <= 10);
+
+        for proxy in proxies.iter().step_by(2) {
+            tree.remove(*proxy);
+        }
+
+        assert_eq!(tree.validate(tree.root), 50);
+        assert!(tree.height() <= 9);
+    }
+
+    #[test]
+    fn test_for_each_pair_finds_overlapping_proxies() {
+        let mut tree = DynamicTree::new();
+
+        tree.insert(&create_square(ZERO, 10.), 0);
+        tree.insert(&create_square(Vec2D { x: 5., y: 5. }, 10.), 1);
+        tree.insert(&create_square(Vec2D { x: 100., y: 0. }, 10.), 2);
+        tree.insert(&create_square(Vec2D { x: 105., y: 0. }, 10.), 3);
+
+        let mut pairs = vec![];
+        tree.for_each_pair(|a, b| pairs.push((a.min(b), a.max(b))));
+        pairs.sort();
+
+        assert_eq!(pairs, vec![(0, 1), (2, 3)]);
+    }
+
+    #[test]
+    fn test_move_proxy_only_reinserts_bodies_leaving_their_fat_bounding_volume() {
+        let mut tree = DynamicTree::new();
+
+        let proxy = tree.insert(&square_at(0), 0);
+        tree.insert(&square_at(1), 1);
+
+        let nudged = create_square(Vec2D { x: 1., y: 0. }, 10.);
+        assert!(!tree.move_proxy(proxy, &nudged, &ZERO));
+
+        let moved = create_square(Vec2D { x: 50., y: 0. }, 10.);
+        assert!(tree.move_proxy(proxy, &moved, &Vec2D { x: 1., y: 0. }));
+        assert!(tree.fat_bounding_volume(proxy).contains(&moved));
+        assert!(tree.fat_bounding_volume(proxy).bottom_right.x >= 60. + 4.);
+
+        let mut found = vec![];
+        tree.query(&moved, |data| found.push(data));
+
+        assert_eq!(found, vec![0]);
+        assert_eq!(tree.validate(tree.root), 2);
+    }
+}
@@ -1,3 +1,7 @@
+mod dynamic_tree;
+
+pub use dynamic_tree::*;
+
 use crate::vec2::Vec2D;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -26,6 +30,66 @@ impl BoundingVolume {
             bottom_right: self.bottom_right.max(&other.bottom_right),
         }
     }
+
+    /// Whether the segment from `start` to `end` passes through the bounding volume.
+    pub fn is_intersecting_segment(&self, start: &Vec2D, end: &Vec2D) -> bool {
+        let direction = end - start;
+
+        // The range of fractions along the segment that are within both slabs.
+        let mut entry: f64 = 0.;
+        let mut exit: f64 = 1.;
+
+        for (start, direction, min, max) in [
+            (start.x, direction.x, self.top_left.x, self.bottom_right.x),
+            (start.y, direction.y, self.top_left.y, self.bottom_right.y),
+        ] {
+            if direction == 0. {
+                if start < min || start > max {
+                    return false;
+                }
+
+                continue;
+            }
+
+            let first = (min - start) / direction;
+            let second = (max - start) / direction;
+
+            entry = entry.max(first.min(second));
+            exit = exit.min(first.max(second));
+
+            if entry > exit {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    pub fn contains(&self, other: &BoundingVolume) -> bool {
+        self.top_left.x <= other.top_left.x
+            && self.top_left.y <= other.top_left.y
+            && other.bottom_right.x <= self.bottom_right.x
+            && other.bottom_right.y <= self.bottom_right.y
+    }
+
+    /// Grows the bounding volume by `margin` on every side.
+    pub fn expand(&self, margin: f64) -> BoundingVolume {
+        let margin = Vec2D {
+            x: margin,
+            y: margin,
+        };
+
+        BoundingVolume {
+            top_left: &self.top_left - &margin,
+            bottom_right: &self.bottom_right + &margin,
+        }
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        let size = &self.bottom_right - &self.top_left;
+
+        2. * (size.x + size.y)
+    }
 }
 
 #[cfg(test)]
@@ -75,6 +139,32 @@ mod tests {
         assert!(!bv1.is_intersecting(&bv2));
     }
 
+    #[test]
+    fn test_is_intersecting_segment_works() {
+        let square = create_square(ZERO, 10.);
+
+        assert!(square.is_intersecting_segment(&Vec2D { x: -5., y: 5. }, &Vec2D { x: 15., y: 5. }));
+        assert!(square.is_intersecting_segment(&Vec2D { x: 5., y: 5. }, &Vec2D { x: 5., y: 6. }));
+        assert!(
+            !square.is_intersecting_segment(&Vec2D { x: -5., y: 5. }, &Vec2D { x: -1., y: 5. })
+        );
+        assert!(
+            !square.is_intersecting_segment(&Vec2D { x: -5., y: 10. }, &Vec2D { x: 10., y: 25. })
+        );
+    }
+
+    #[test]
+    fn test_contains_works() {
+        let outer = create_square(ZERO, 10.);
+        let inner = create_square(Vec2D { x: 5., y: 5. }, 5.);
+        let overlapping = create_square(Vec2D { x: 5., y: 5. }, 10.);
+
+        assert!(outer.contains(&inner));
+        assert!(outer.contains(&outer));
+        assert!(!outer.contains(&overlapping));
+        assert!(!inner.contains(&outer));
+    }
+
     #[test]
     fn test_union_works() {
         let bv1 = create_square(ZERO, 10.);
@@ -1,5 +1,7 @@
+pub mod arena;
 pub mod body;
 pub mod bounding_volume;
+#[cfg(feature = "macroquad")]
 pub mod rendering;
 pub mod simulation;
 pub mod vec2;
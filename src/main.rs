@@ -1,4 +1,8 @@
-use physics_engine::{rendering::*, simulation::world::World, vec2::Vec2D};
+use physics_engine::{
+    rendering::*,
+    simulation::world::{GenerationSpec, World},
+    vec2::Vec2D,
+};
 
 use macroquad::prelude::*;
 
@@ -18,13 +22,15 @@ fn window_conf() -> Conf {
 const TIME_BETWEEN_TICKS: f32 = 10. / 1_000.;
 
 fn generate_world() -> World {
-    World::generate(
-        screen_width() as f64,
-        screen_height() as f64,
-        10.,
-        500,
-        Vec2D { x: 0., y: 100. },
-    )
+    World::generate(&GenerationSpec {
+        seed: macroquad::rand::rand() as u64,
+        width: screen_width() as f64,
+        height: screen_height() as f64,
+        offset: 10.,
+        num_bodies: 1000,
+        gravity: Vec2D { x: 0., y: 100. },
+        ..GenerationSpec::default()
+    })
 }
 
 #[derive(Debug)]
@@ -148,7 +154,7 @@ async fn main() {
             RED,
         );
         draw_text(
-            format!("{} bodies", world.dynamic_bodies.len()).as_str(),
+            format!("{} bodies", world.dynamic_body_count()).as_str(),
             10.,
             90.,
             16.,
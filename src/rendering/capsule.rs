@@ -0,0 +1,39 @@
+use macroquad::prelude::*;
+
+use crate::vec2::Vec2D;
+
+pub fn render_capsule(capsule: &crate::body::Capsule) {
+    let [start, end] = capsule.endpoints();
+
+    let (sin, cos) = capsule.body.orientation.sin_cos();
+    let offset = &Vec2D { x: sin, y: -cos } * capsule.radius;
+
+    for side in [&offset, &-offset] {
+        let side_start = &start + side;
+        let side_end = &end + side;
+
+        draw_line(
+            side_start.x as f32,
+            side_start.y as f32,
+            side_end.x as f32,
+            side_end.y as f32,
+            1.,
+            BLACK,
+        );
+    }
+
+    let orientation = capsule.body.orientation.to_degrees() as f32;
+
+    for (centre, rotation) in [(end, orientation - 90.), (start, orientation + 90.)] {
+        draw_arc(
+            centre.x as f32,
+            centre.y as f32,
+            40,
+            capsule.radius as f32,
+            rotation,
+            1.,
+            180.,
+            BLACK,
+        );
+    }
+}
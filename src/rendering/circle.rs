@@ -1,5 +1,7 @@
 use macroquad::prelude::*;
 
+use crate::vec2::Vec2D;
+
 pub fn render_circle(circle: &crate::body::Circle) {
     draw_poly_lines(
         circle.body.position.x as f32,
@@ -10,4 +12,20 @@ pub fn render_circle(circle: &crate::body::Circle) {
         1.,
         BLACK,
     );
+
+    let edge = &circle.body.position
+        + &Vec2D {
+            x: circle.radius,
+            y: 0.,
+        }
+        .rotate(circle.body.orientation);
+
+    draw_line(
+        circle.body.position.x as f32,
+        circle.body.position.y as f32,
+        edge.x as f32,
+        edge.y as f32,
+        1.,
+        BLACK,
+    );
 }
@@ -1,21 +1,31 @@
+mod capsule;
 mod circle;
 mod line;
+mod polygon;
 mod rectangle;
+mod segment;
 
 use crate::body::{DynamicBody, StaticBody};
 use crate::simulation::world::World;
 
 pub fn render_world(world: &World) {
-    for body in &world.static_bodies {
+    for (_, body) in world.static_bodies() {
         match body {
             StaticBody::Line(line) => line::render_line(line),
+            StaticBody::Segment(segment) => segment::render_segment(segment),
+            StaticBody::Chain(chain) => segment::render_chain(chain),
+            StaticBody::Circle(circle) => circle::render_circle(circle),
+            StaticBody::Rectangle(rectangle) => rectangle::render_rectangle(rectangle),
+            StaticBody::Polygon(polygon) => polygon::render_polygon(polygon),
         }
     }
 
-    for body in &world.dynamic_bodies {
+    for (_, body) in world.dynamic_bodies() {
         match body {
             DynamicBody::Circle(circle) => circle::render_circle(circle),
             DynamicBody::Rectangle(rectangle) => rectangle::render_rectangle(rectangle),
+            DynamicBody::Polygon(polygon) => polygon::render_polygon(polygon),
+            DynamicBody::Capsule(capsule) => capsule::render_capsule(capsule),
         }
     }
 }
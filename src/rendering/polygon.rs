@@ -0,0 +1,18 @@
+use macroquad::prelude::*;
+
+pub fn render_polygon(polygon: &crate::body::Polygon) {
+    let vertices: Vec<_> = polygon.world_vertices().collect();
+
+    for (i, start) in vertices.iter().enumerate() {
+        let end = &vertices[(i + 1) % vertices.len()];
+
+        draw_line(
+            start.x as f32,
+            start.y as f32,
+            end.x as f32,
+            end.y as f32,
+            1.,
+            BLACK,
+        );
+    }
+}
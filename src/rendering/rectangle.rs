@@ -1,11 +1,18 @@
 use macroquad::prelude::*;
 
 pub fn render_rectangle(rectangle: &crate::body::Rectangle) {
-    let x = (rectangle.body.position.x - rectangle.half_width) as f32;
-    let y = (rectangle.body.position.y - rectangle.half_height) as f32;
+    let corners = rectangle.corners();
 
-    let width = (rectangle.half_width * 2.) as f32;
-    let height = (rectangle.half_height * 2.) as f32;
+    for (i, start) in corners.iter().enumerate() {
+        let end = &corners[(i + 1) % corners.len()];
 
-    draw_rectangle_lines(x, y, width, height, 1., BLACK);
+        draw_line(
+            start.x as f32,
+            start.y as f32,
+            end.x as f32,
+            end.y as f32,
+            1.,
+            BLACK,
+        );
+    }
 }
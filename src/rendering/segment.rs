@@ -0,0 +1,20 @@
+use macroquad::prelude::*;
+
+use crate::body::{Chain, Segment};
+
+pub fn render_segment(segment: &Segment) {
+    draw_line(
+        segment.start().x as f32,
+        segment.start().y as f32,
+        segment.end().x as f32,
+        segment.end().y as f32,
+        1.,
+        BLACK,
+    );
+}
+
+pub fn render_chain(chain: &Chain) {
+    for segment in chain.segments() {
+        render_segment(&segment);
+    }
+}
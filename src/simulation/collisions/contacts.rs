@@ -0,0 +1,270 @@
+use super::hull::*;
+use super::*;
+
+pub fn circle_circle(this: &Circle, that: &Circle) -> Option<ContactManifold> {
+    let this_to_that = &that.body.position - &this.body.position;
+    let length = this_to_that.length();
+
+    let distance = length - (this.radius + that.radius);
+
+    let normal = &this_to_that / length;
+    let position = &this.body.position + &(&normal * (this.radius + distance / 2.));
+
+    ContactManifold::from_touching_points(
+        normal,
+        &[ContactPoint {
+            position,
+            distance,
+            id: 0,
+        }],
+    )
+}
+
+pub fn rectangle_rectangle(this: &Rectangle, that: &Rectangle) -> Option<ContactManifold> {
+    hull_hull(&Hull::from_rectangle(this), &Hull::from_rectangle(that))
+}
+
+pub fn circle_rectangle(this: &Circle, that: &Rectangle) -> Option<ContactManifold> {
+    // Work in the rectangle's frame of reference, where it is axis-aligned.
+    let orientation = that.body.orientation;
+    let displacement = (&that.body.position - &this.body.position).rotate(-orientation);
+
+    let clamped_displacement = displacement.clamp(
+        &Vec2D {
+            x: -that.half_width,
+            y: -that.half_height,
+        },
+        &Vec2D {
+            x: that.half_width,
+            y: that.half_height,
+        },
+    );
+
+    let is_inside = clamped_displacement == displacement;
+
+    let closest_point = if is_inside {
+        if that.half_width - displacement.x.abs() < that.half_height - displacement.y.abs() {
+            Vec2D {
+                x: clamped_displacement.x.signum() * that.half_width,
+                y: clamped_displacement.y,
+            }
+        } else {
+            Vec2D {
+                x: clamped_displacement.x,
+                y: clamped_displacement.y.signum() * that.half_height,
+            }
+        }
+    } else {
+        clamped_displacement
+    };
+
+    let normal = &displacement - &closest_point;
+    let length = normal.length();
+
+    let position = &that.body.position - &closest_point.rotate(orientation);
+    let normal = (&normal / length).rotate(orientation);
+
+    let (normal, distance) = if is_inside {
+        (-normal, -length - this.radius)
+    } else {
+        (normal, length - this.radius)
+    };
+
+    ContactManifold::from_touching_points(
+        normal,
+        &[ContactPoint {
+            position,
+            distance,
+            id: 0,
+        }],
+    )
+}
+
+pub fn circle_polygon(this: &Circle, that: &Polygon) -> Option<ContactManifold> {
+    hull_circle(&Hull::from_polygon(that), &this.body.position, this.radius)
+        .map(ContactManifold::flip)
+}
+
+pub fn rectangle_polygon(this: &Rectangle, that: &Polygon) -> Option<ContactManifold> {
+    hull_hull(&Hull::from_rectangle(this), &Hull::from_polygon(that))
+}
+
+pub fn polygon_polygon(this: &Polygon, that: &Polygon) -> Option<ContactManifold> {
+    hull_hull(&Hull::from_polygon(this), &Hull::from_polygon(that))
+}
+
+pub fn circle_capsule(this: &Circle, that: &Capsule) -> Option<ContactManifold> {
+    hull_circle(&Hull::from_capsule(that), &this.body.position, this.radius)
+        .map(ContactManifold::flip)
+}
+
+pub fn rectangle_capsule(this: &Rectangle, that: &Capsule) -> Option<ContactManifold> {
+    hull_hull(&Hull::from_rectangle(this), &Hull::from_capsule(that))
+}
+
+pub fn polygon_capsule(this: &Polygon, that: &Capsule) -> Option<ContactManifold> {
+    hull_hull(&Hull::from_polygon(this), &Hull::from_capsule(that))
+}
+
+pub fn capsule_capsule(this: &Capsule, that: &Capsule) -> Option<ContactManifold> {
+    hull_hull(&Hull::from_capsule(this), &Hull::from_capsule(that))
+}
+
+pub fn line_circle(this: &Line, that: &Circle) -> Option<ContactManifold> {
+    let distance =
+        this.normal.dot_product(&that.body.position) + this.origin_distance - that.radius;
+
+    ContactManifold::from_touching_points(
+        this.normal,
+        &[ContactPoint {
+            position: &that.body.position - &(&this.normal * that.radius),
+            distance,
+            id: 0,
+        }],
+    )
+}
+
+pub fn line_rectangle(this: &Line, that: &Rectangle) -> Option<ContactManifold> {
+    line_vertices(this, &that.corners())
+}
+
+pub fn line_polygon(this: &Line, that: &Polygon) -> Option<ContactManifold> {
+    let mut vertices = [ZERO; MAX_POLYGON_VERTICES];
+    let mut vertex_count = 0;
+
+    for vertex in that.world_vertices() {
+        vertices[vertex_count] = vertex;
+        vertex_count += 1;
+    }
+
+    line_vertices(this, &vertices[..vertex_count])
+}
+
+pub fn line_capsule(this: &Line, that: &Capsule) -> Option<ContactManifold> {
+    let offset = &this.normal * that.radius;
+
+    let [start, end] = that.endpoints().map(|endpoint| ContactPoint {
+        position: &endpoint - &offset,
+        distance: this.normal.dot_product(&endpoint) + this.origin_distance - that.radius,
+        id: 0,
+    });
+
+    let points = [start, ContactPoint { id: 1, ..end }];
+
+    ContactManifold::from_touching_points(this.normal, &points)
+}
+
+pub fn segment_circle(this: &Segment, that: &Circle) -> Option<ContactManifold> {
+    let side = segment_side(this, &that.body.position)?;
+    let manifold = hull_circle(&segment_hull(this), &that.body.position, that.radius)?;
+
+    // A circle past the end of the segment can only touch its end vertex, so there is no face
+    // contact to fall back on.
+    smooth_segment_contact(this, side, manifold, || None)
+}
+
+pub fn segment_rectangle(this: &Segment, that: &Rectangle) -> Option<ContactManifold> {
+    segment_hull_contact(this, &Hull::from_rectangle(that), &that.body.position)
+}
+
+pub fn segment_polygon(this: &Segment, that: &Polygon) -> Option<ContactManifold> {
+    segment_hull_contact(this, &Hull::from_polygon(that), &that.body.position)
+}
+
+pub fn segment_capsule(this: &Segment, that: &Capsule) -> Option<ContactManifold> {
+    segment_hull_contact(this, &Hull::from_capsule(that), &that.body.position)
+}
+
+fn segment_hull(this: &Segment) -> Hull {
+    Hull::from_segment([this.start(), this.end()], 0.)
+}
+
+fn segment_hull_contact(this: &Segment, that: &Hull, centre: &Vec2D) -> Option<ContactManifold> {
+    let side = segment_side(this, centre)?;
+    let hull = segment_hull(this);
+    let manifold = hull_hull(&hull, that)?;
+
+    smooth_segment_contact(this, side, manifold, || {
+        hull_hull_on_face(&hull, side, that)
+    })
+}
+
+/// The face of the segment's hull (0 for the front, 1 for the back) on the same side as `centre`,
+/// or `None` if a one-sided segment should ignore a body there.
+fn segment_side(this: &Segment, centre: &Vec2D) -> Option<usize> {
+    let is_in_front = this.normal().dot_product(&(centre - &this.start())) >= 0.;
+
+    match (is_in_front, this.two_sided) {
+        (true, _) => Some(0),
+        (false, true) => Some(1),
+        (false, false) => None,
+    }
+}
+
+/// The cosine of the largest angle between a contact normal and the segment's normal for which
+/// the contact is considered to be on the segment's face rather than at one of its ends.
+const SEGMENT_FACE_THRESHOLD: f64 = 0.999;
+
+/// Handles the contacts at the ends of a segment which has neighbours in a chain.
+///
+/// A vertex between two segments belongs to the segment that ends there if the chain is convex
+/// there, and to neither otherwise, as the faces of the segments already cover it. Contacts that
+/// do not belong to this segment use `face_contact` instead, which only has the segment's face.
+fn smooth_segment_contact(
+    this: &Segment,
+    side: usize,
+    manifold: ContactManifold,
+    face_contact: impl FnOnce() -> Option<ContactManifold>,
+) -> Option<ContactManifold> {
+    let (face_normal, side_sign) = if side == 0 {
+        (this.normal(), 1.)
+    } else {
+        (-this.normal(), -1.)
+    };
+
+    if manifold.normal.dot_product(&face_normal) >= SEGMENT_FACE_THRESHOLD {
+        return Some(manifold);
+    }
+
+    let edge = &this.end() - &this.start();
+    let is_at_start = manifold.normal.dot_product(&edge) < 0.;
+
+    let turn = match (is_at_start, this.ghost_start, this.ghost_end) {
+        (true, Some(ghost), _) => (&this.start() - &ghost).cross_product(&edge),
+        (false, _, Some(ghost)) => edge.cross_product(&(&ghost - &this.end())),
+        _ => return Some(manifold),
+    };
+
+    let is_convex = turn * side_sign > 0.;
+
+    match (is_convex, is_at_start) {
+        (true, false) => Some(manifold),
+        (true, true) => None,
+        (false, _) => face_contact(),
+    }
+}
+
+/// Contact between a line and the convex shape made of `vertices`, using the two deepest ones.
+fn line_vertices(this: &Line, vertices: &[Vec2D]) -> Option<ContactManifold> {
+    let mut deepest = [ContactPoint {
+        position: ZERO,
+        distance: f64::INFINITY,
+        id: 0,
+    }; MAX_MANIFOLD_POINTS];
+
+    for (i, vertex) in vertices.iter().enumerate() {
+        let point = ContactPoint {
+            position: *vertex,
+            distance: this.normal.dot_product(vertex) + this.origin_distance,
+            id: i as u32,
+        };
+
+        if point.distance < deepest[0].distance {
+            deepest = [point, deepest[0]];
+        } else if point.distance < deepest[1].distance {
+            deepest[1] = point;
+        }
+    }
+
+    ContactManifold::from_touching_points(this.normal, &deepest)
+}
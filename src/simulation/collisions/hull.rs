@@ -0,0 +1,427 @@
+use crate::body::*;
+use crate::vec2::*;
+
+use super::{ContactManifold, ContactPoint};
+
+/// How much deeper the second shape's best face has to be before it is used as the reference
+/// face. This keeps the choice stable when two faces are nearly parallel.
+const REFERENCE_FACE_TOLERANCE: f64 = 0.005;
+
+/// The cosine of the angle under which two rounded faces are considered parallel, in which case
+/// they get two contact points instead of one.
+const PARALLEL_FACES_THRESHOLD: f64 = 0.999;
+
+/// A convex shape in world space, optionally inflated by `radius`. This is used to share the
+/// separating axis and clipping logic between all the shapes that are not circles.
+#[derive(Debug, Clone, Copy)]
+pub struct Hull {
+    vertices: [Vec2D; MAX_POLYGON_VERTICES],
+    normals: [Vec2D; MAX_POLYGON_VERTICES],
+    count: usize,
+    radius: f64,
+}
+
+impl Hull {
+    pub fn from_rectangle(rectangle: &Rectangle) -> Self {
+        let [x_axis, y_axis] = rectangle.axes();
+
+        let mut hull = Self {
+            vertices: [ZERO; MAX_POLYGON_VERTICES],
+            normals: [ZERO; MAX_POLYGON_VERTICES],
+            count: 4,
+            radius: 0.,
+        };
+
+        hull.vertices[..4].copy_from_slice(&rectangle.corners());
+        hull.normals[..4].copy_from_slice(&[-y_axis, x_axis, y_axis, -x_axis]);
+
+        hull
+    }
+
+    pub fn from_polygon(polygon: &Polygon) -> Self {
+        let mut hull = Self {
+            vertices: [ZERO; MAX_POLYGON_VERTICES],
+            normals: [ZERO; MAX_POLYGON_VERTICES],
+            count: polygon.local_vertices().len(),
+            radius: 0.,
+        };
+
+        let orientation = polygon.body.orientation;
+
+        for (i, (vertex, normal)) in polygon
+            .local_vertices()
+            .iter()
+            .zip(polygon.local_normals())
+            .enumerate()
+        {
+            hull.vertices[i] = &polygon.body.position + &vertex.rotate(orientation);
+            hull.normals[i] = normal.rotate(orientation);
+        }
+
+        hull
+    }
+
+    pub fn from_capsule(capsule: &Capsule) -> Self {
+        Self::from_segment(capsule.endpoints(), capsule.radius)
+    }
+
+    /// A degenerate hull with two vertices and two opposite normals.
+    pub fn from_segment([start, end]: [Vec2D; 2], radius: f64) -> Self {
+        let direction = &end - &start;
+        let normal = &Vec2D {
+            x: direction.y,
+            y: -direction.x,
+        } / direction.length();
+
+        let mut hull = Self {
+            vertices: [ZERO; MAX_POLYGON_VERTICES],
+            normals: [ZERO; MAX_POLYGON_VERTICES],
+            count: 2,
+            radius,
+        };
+
+        hull.vertices[..2].copy_from_slice(&[start, end]);
+        hull.normals[..2].copy_from_slice(&[normal, -normal]);
+
+        hull
+    }
+
+    fn vertices(&self) -> &[Vec2D] {
+        &self.vertices[..self.count]
+    }
+
+    fn normals(&self) -> &[Vec2D] {
+        &self.normals[..self.count]
+    }
+
+    fn edge(&self, index: usize) -> (Vec2D, Vec2D) {
+        (
+            self.vertices[index],
+            self.vertices[(index + 1) % self.count],
+        )
+    }
+
+    /// The edge of `self` whose normal best separates `other`, and by how much.
+    fn find_max_separation(&self, other: &Self) -> (usize, f64) {
+        let mut best_index = 0;
+        let mut best_separation = f64::NEG_INFINITY;
+
+        for (i, (vertex, normal)) in self.vertices().iter().zip(self.normals()).enumerate() {
+            let separation = other
+                .vertices()
+                .iter()
+                .map(|other_vertex| normal.dot_product(&(other_vertex - vertex)))
+                .fold(f64::INFINITY, f64::min);
+
+            if separation > best_separation {
+                best_index = i;
+                best_separation = separation;
+            }
+        }
+
+        (best_index, best_separation)
+    }
+
+    /// The closest points between the cores (i.e. without the radius) of `self` and `other`,
+    /// assuming that they are not overlapping.
+    fn closest_points(&self, other: &Self) -> (Vec2D, Vec2D) {
+        let mut best = (ZERO, ZERO);
+        let mut best_distance_squared = f64::INFINITY;
+
+        for i in 0..self.edge_count() {
+            for j in 0..other.edge_count() {
+                let (this_point, other_point) =
+                    closest_points_between_segments(self.edge(i), other.edge(j));
+
+                let distance_squared = (&other_point - &this_point).length_squared();
+
+                if distance_squared < best_distance_squared {
+                    best = (this_point, other_point);
+                    best_distance_squared = distance_squared;
+                }
+            }
+        }
+
+        best
+    }
+
+    fn edge_count(&self) -> usize {
+        // The two edges of a segment are the same.
+        if self.count == 2 { 1 } else { self.count }
+    }
+}
+
+/// Contact between two convex hulls, with the normal pointing from `this` to `that`.
+///
+/// If the cores overlap, the reference face is the one with the largest separation, and the
+/// contact points are found by clipping the most anti-parallel (incident) edge of the other hull
+/// against it. Otherwise, only the rounded parts are touching, so the contact is between the
+/// closest points of the cores, unless the faces are parallel (e.g. a capsule lying on a box).
+pub fn hull_hull(this: &Hull, that: &Hull) -> Option<ContactManifold> {
+    let total_radius = this.radius + that.radius;
+
+    let (this_edge, this_separation) = this.find_max_separation(that);
+
+    if this_separation > total_radius {
+        return None;
+    }
+
+    let (that_edge, that_separation) = that.find_max_separation(this);
+
+    if that_separation > total_radius {
+        return None;
+    }
+
+    let (reference, incident, reference_edge, flip) =
+        if that_separation > this_separation + REFERENCE_FACE_TOLERANCE {
+            (that, this, that_edge, true)
+        } else {
+            (this, that, this_edge, false)
+        };
+
+    // The face normals of two parallel segments miss the axis along them, so collinear segments
+    // have a separation of 0 even when their cores are apart. Their distance tells them apart.
+    if total_radius > 0. && this_separation.max(that_separation) >= 0. {
+        let (this_point, that_point) = this.closest_points(that);
+
+        let displacement = &that_point - &this_point;
+        let length = displacement.length();
+
+        if length >= total_radius {
+            return None;
+        }
+
+        // Touching cores are clipped like overlapping ones.
+        if length > 0. {
+            let normal = &displacement / length;
+
+            let face_normal = if flip {
+                -reference.normals[reference_edge]
+            } else {
+                reference.normals[reference_edge]
+            };
+
+            if face_normal.dot_product(&normal) > PARALLEL_FACES_THRESHOLD
+                && let Some(manifold) =
+                    clip_incident_edge(reference, incident, reference_edge, flip)
+            {
+                return Some(if flip { manifold.flip() } else { manifold });
+            }
+
+            return Some(ContactManifold::new(
+                normal,
+                &[ContactPoint {
+                    position: &that_point - &(&normal * that.radius),
+                    distance: length - total_radius,
+                    id: 0,
+                }],
+            ));
+        }
+    }
+
+    let manifold = clip_incident_edge(reference, incident, reference_edge, flip)?;
+
+    Some(if flip { manifold.flip() } else { manifold })
+}
+
+/// Contact between two convex hulls, like [`hull_hull`], but with the reference face forced to be
+/// the given face of `this`.
+pub fn hull_hull_on_face(this: &Hull, face: usize, that: &Hull) -> Option<ContactManifold> {
+    clip_incident_edge(this, that, face, false)
+}
+
+/// Clips the most anti-parallel edge of `incident` to the side planes of the reference edge, and
+/// keeps the points that are behind the reference face. The normal points from `reference` to
+/// `incident`, and `flip` is only used to identify the points.
+fn clip_incident_edge(
+    reference: &Hull,
+    incident: &Hull,
+    reference_edge: usize,
+    flip: bool,
+) -> Option<ContactManifold> {
+    let normal = reference.normals[reference_edge];
+    let total_radius = reference.radius + incident.radius;
+
+    let incident_edge = incident
+        .normals()
+        .iter()
+        .enumerate()
+        .map(|(i, incident_normal)| (i, incident_normal.dot_product(&normal)))
+        .fold((0, f64::INFINITY), |best, candidate| {
+            if candidate.1 < best.1 {
+                candidate
+            } else {
+                best
+            }
+        })
+        .0;
+
+    let (reference_start, reference_end) = reference.edge(reference_edge);
+    let (incident_start, incident_end) = incident.edge(incident_edge);
+
+    let tangent = &reference_end - &reference_start;
+    let tangent = &tangent / tangent.length();
+
+    let clipped = clip_segment(
+        [incident_start, incident_end],
+        &-tangent,
+        -tangent.dot_product(&reference_start),
+    )?;
+    let clipped = clip_segment(clipped, &tangent, tangent.dot_product(&reference_end))?;
+
+    // Move the points from the incident core to the incident surface.
+    let [start, end] = clipped.map(|point| ContactPoint {
+        position: &point - &(&normal * incident.radius),
+        distance: normal.dot_product(&(&point - &reference_start)) - total_radius,
+        id: 0,
+    });
+
+    let id = |incident_vertex: usize| {
+        let flip = if flip { 1 } else { 0 };
+
+        (flip << 24 | reference_edge << 16 | incident_edge << 8 | incident_vertex) as u32
+    };
+
+    let points = [
+        ContactPoint { id: id(0), ..start },
+        ContactPoint { id: id(1), ..end },
+    ];
+
+    ContactManifold::from_touching_points(normal, &points)
+}
+
+/// Contact between a convex hull and a circle, with the normal pointing from the hull to the
+/// circle.
+pub fn hull_circle(this: &Hull, centre: &Vec2D, radius: f64) -> Option<ContactManifold> {
+    let total_radius = this.radius + radius;
+
+    let mut best_index = 0;
+    let mut best_separation = f64::NEG_INFINITY;
+
+    for (i, (vertex, normal)) in this.vertices().iter().zip(this.normals()).enumerate() {
+        let separation = normal.dot_product(&(centre - vertex));
+
+        if separation > best_separation {
+            best_index = i;
+            best_separation = separation;
+        }
+    }
+
+    if best_separation > total_radius {
+        return None;
+    }
+
+    let (start, end) = this.edge(best_index);
+
+    // A centre inside a polygon always projects onto its closest edge, but one in line with a
+    // segment is only inside it if it projects onto it.
+    let (normal, distance) = if (centre - &start).dot_product(&(&end - &start)) <= 0. {
+        closest_vertex_normal(&start, centre, total_radius)?
+    } else if (centre - &end).dot_product(&(&start - &end)) <= 0. {
+        closest_vertex_normal(&end, centre, total_radius)?
+    } else {
+        (this.normals[best_index], best_separation - total_radius)
+    };
+
+    if distance >= 0. {
+        return None;
+    }
+
+    Some(ContactManifold::new(
+        normal,
+        &[ContactPoint {
+            position: centre - &(&normal * radius),
+            distance,
+            id: 0,
+        }],
+    ))
+}
+
+fn closest_vertex_normal(vertex: &Vec2D, centre: &Vec2D, radius: f64) -> Option<(Vec2D, f64)> {
+    let displacement = centre - vertex;
+    let length = displacement.length();
+
+    if length > radius {
+        return None;
+    }
+
+    Some((&displacement / length, length - radius))
+}
+
+/// The closest points between two segments, from the first to the second.
+pub fn closest_points_between_segments(
+    (this_start, this_end): (Vec2D, Vec2D),
+    (that_start, that_end): (Vec2D, Vec2D),
+) -> (Vec2D, Vec2D) {
+    let this_direction = &this_end - &this_start;
+    let that_direction = &that_end - &that_start;
+    let start_offset = &this_start - &that_start;
+
+    let this_length_squared = this_direction.length_squared();
+    let that_length_squared = that_direction.length_squared();
+
+    let this_dot_offset = this_direction.dot_product(&start_offset);
+    let that_dot_offset = that_direction.dot_product(&start_offset);
+
+    let (s, t) = if this_length_squared == 0. && that_length_squared == 0. {
+        (0., 0.)
+    } else if this_length_squared == 0. {
+        (0., (that_dot_offset / that_length_squared).clamp(0., 1.))
+    } else if that_length_squared == 0. {
+        ((-this_dot_offset / this_length_squared).clamp(0., 1.), 0.)
+    } else {
+        let directions_dot = this_direction.dot_product(&that_direction);
+        let denominator =
+            this_length_squared * that_length_squared - directions_dot * directions_dot;
+
+        // Pick an arbitrary point on the first segment if they are parallel.
+        let s = if denominator != 0. {
+            ((directions_dot * that_dot_offset - this_dot_offset * that_length_squared)
+                / denominator)
+                .clamp(0., 1.)
+        } else {
+            0.
+        };
+
+        let t = (directions_dot * s + that_dot_offset) / that_length_squared;
+
+        if t < 0. {
+            ((-this_dot_offset / this_length_squared).clamp(0., 1.), 0.)
+        } else if t > 1. {
+            (
+                ((directions_dot - this_dot_offset) / this_length_squared).clamp(0., 1.),
+                1.,
+            )
+        } else {
+            (s, t)
+        }
+    };
+
+    (
+        &this_start + &(&this_direction * s),
+        &that_start + &(&that_direction * t),
+    )
+}
+
+/// Keeps the part of the segment where `normal . point <= offset`.
+fn clip_segment(segment: [Vec2D; 2], normal: &Vec2D, offset: f64) -> Option<[Vec2D; 2]> {
+    let [start, end] = segment;
+
+    let start_distance = normal.dot_product(&start) - offset;
+    let end_distance = normal.dot_product(&end) - offset;
+
+    match (start_distance <= 0., end_distance <= 0.) {
+        (true, true) => Some(segment),
+        (false, false) => None,
+        (start_inside, _) => {
+            let t = start_distance / (start_distance - end_distance);
+            let intersection = &start + &(&(&end - &start) * t);
+
+            if start_inside {
+                Some([start, intersection])
+            } else {
+                Some([intersection, end])
+            }
+        }
+    }
+}
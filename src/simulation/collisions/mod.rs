@@ -1,13 +1,85 @@
+mod contacts;
+mod hull;
+mod point;
+mod raycast;
+
+pub use point::*;
+pub use raycast::*;
+
 use crate::body::*;
 use crate::vec2::*;
 
+pub const MAX_MANIFOLD_POINTS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactPoint {
+    pub position: Vec2D,
+    pub distance: f64,
+    /// Identifies the features (e.g. vertices or edges) of the two shapes that produced this
+    /// point, so that it can be matched with the same point on the next tick.
+    pub id: u32,
+}
+
+/// The touching points between two bodies, which share a normal pointing from the first body to
+/// the second.
 #[derive(Debug, Clone, Copy)]
-pub struct Contact {
+pub struct ContactManifold {
     pub normal: Vec2D,
-    pub distance: f64,
+    points: [ContactPoint; MAX_MANIFOLD_POINTS],
+    point_count: usize,
 }
 
-impl Contact {
+impl ContactManifold {
+    /// Panics if there are no points or more than [`MAX_MANIFOLD_POINTS`].
+    pub fn new(normal: Vec2D, points: &[ContactPoint]) -> Self {
+        assert!(!points.is_empty() && points.len() <= MAX_MANIFOLD_POINTS);
+
+        let mut manifold = Self {
+            normal,
+            points: [points[0]; MAX_MANIFOLD_POINTS],
+            point_count: points.len(),
+        };
+
+        manifold.points[..points.len()].copy_from_slice(points);
+
+        manifold
+    }
+
+    /// Keeps the points of `candidates` that are touching (i.e. with a negative distance), and
+    /// returns `None` if there are none.
+    pub fn from_touching_points(normal: Vec2D, candidates: &[ContactPoint]) -> Option<Self> {
+        let mut points = [ContactPoint {
+            position: ZERO,
+            distance: 0.,
+            id: 0,
+        }; MAX_MANIFOLD_POINTS];
+        let mut point_count = 0;
+
+        for point in candidates.iter().filter(|point| point.distance < 0.) {
+            if point_count == MAX_MANIFOLD_POINTS {
+                break;
+            }
+
+            points[point_count] = *point;
+            point_count += 1;
+        }
+
+        (point_count > 0).then(|| Self::new(normal, &points[..point_count]))
+    }
+
+    pub fn points(&self) -> &[ContactPoint] {
+        &self.points[..self.point_count]
+    }
+
+    /// The point with the largest penetration.
+    pub fn deepest_point(&self) -> &ContactPoint {
+        // Safe because there is always at least one point
+        self.points()
+            .iter()
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+            .unwrap()
+    }
+
     pub fn flip(mut self) -> Self {
         self.normal = -self.normal;
 
@@ -15,167 +87,481 @@ impl Contact {
     }
 }
 
-pub fn generate_contact_static(this: &StaticBody, that: &DynamicBody) -> Contact {
-    match (this, that) {
-        (StaticBody::Line(this), DynamicBody::Circle(that)) => contacts::line_circle(this, that),
-        (StaticBody::Line(this), DynamicBody::Rectangle(that)) => {
-            contacts::line_rectangle(this, that)
+/// The contact between a part of a static body (see [`StaticBody::part_count`]) and a dynamic
+/// body.
+pub fn generate_contact_static(
+    this: &StaticBody,
+    part: usize,
+    that: &DynamicBody,
+) -> Option<ContactManifold> {
+    match this {
+        StaticBody::Line(this) => line_contact(this, that),
+        StaticBody::Segment(this) => segment_contact(this, that),
+        StaticBody::Chain(this) => segment_contact(&this.segment(part), that),
+        StaticBody::Circle(this) => generate_contact(Shape::Circle(this), that.into()),
+        StaticBody::Rectangle(this) => generate_contact(Shape::Rectangle(this), that.into()),
+        StaticBody::Polygon(this) => generate_contact(Shape::Polygon(this), that.into()),
+    }
+}
+
+fn line_contact(this: &Line, that: &DynamicBody) -> Option<ContactManifold> {
+    match that {
+        DynamicBody::Circle(that) => contacts::line_circle(this, that),
+        DynamicBody::Rectangle(that) => contacts::line_rectangle(this, that),
+        DynamicBody::Polygon(that) => contacts::line_polygon(this, that),
+        DynamicBody::Capsule(that) => contacts::line_capsule(this, that),
+    }
+}
+
+fn segment_contact(this: &Segment, that: &DynamicBody) -> Option<ContactManifold> {
+    match that {
+        DynamicBody::Circle(that) => contacts::segment_circle(this, that),
+        DynamicBody::Rectangle(that) => contacts::segment_rectangle(this, that),
+        DynamicBody::Polygon(that) => contacts::segment_polygon(this, that),
+        DynamicBody::Capsule(that) => contacts::segment_capsule(this, that),
+    }
+}
+
+pub fn generate_contact_dynamic(this: &DynamicBody, that: &DynamicBody) -> Option<ContactManifold> {
+    generate_contact(this.into(), that.into())
+}
+
+/// A borrowed solid shape, which may belong to a dynamic or a static body.
+#[derive(Clone, Copy)]
+enum Shape<'a> {
+    Circle(&'a Circle),
+    Rectangle(&'a Rectangle),
+    Polygon(&'a Polygon),
+    Capsule(&'a Capsule),
+}
+
+impl<'a> From<&'a DynamicBody> for Shape<'a> {
+    fn from(body: &'a DynamicBody) -> Self {
+        match body {
+            DynamicBody::Circle(circle) => Shape::Circle(circle),
+            DynamicBody::Rectangle(rectangle) => Shape::Rectangle(rectangle),
+            DynamicBody::Polygon(polygon) => Shape::Polygon(polygon),
+            DynamicBody::Capsule(capsule) => Shape::Capsule(capsule),
         }
     }
 }
 
-pub fn generate_contact_dynamic(this: &DynamicBody, that: &DynamicBody) -> Option<Contact> {
-    use DynamicBody::*;
+fn generate_contact(this: Shape, that: Shape) -> Option<ContactManifold> {
+    use Shape::*;
 
     match (this, that) {
-        (Circle(this), Circle(that)) => Some(contacts::circle_circle(this, that)),
+        (Circle(this), Circle(that)) => contacts::circle_circle(this, that),
         (Rectangle(this), Rectangle(that)) => contacts::rectangle_rectangle(this, that),
-        (Circle(this), Rectangle(that)) => Some(contacts::circle_rectangle(this, that)),
-        (Rectangle(this), Circle(that)) => Some(contacts::circle_rectangle(that, this).flip()),
+        (Circle(this), Rectangle(that)) => contacts::circle_rectangle(this, that),
+        (Rectangle(this), Circle(that)) => {
+            contacts::circle_rectangle(that, this).map(ContactManifold::flip)
+        }
+        (Polygon(this), Polygon(that)) => contacts::polygon_polygon(this, that),
+        (Circle(this), Polygon(that)) => contacts::circle_polygon(this, that),
+        (Polygon(this), Circle(that)) => {
+            contacts::circle_polygon(that, this).map(ContactManifold::flip)
+        }
+        (Rectangle(this), Polygon(that)) => contacts::rectangle_polygon(this, that),
+        (Polygon(this), Rectangle(that)) => {
+            contacts::rectangle_polygon(that, this).map(ContactManifold::flip)
+        }
+        (Capsule(this), Capsule(that)) => contacts::capsule_capsule(this, that),
+        (Circle(this), Capsule(that)) => contacts::circle_capsule(this, that),
+        (Capsule(this), Circle(that)) => {
+            contacts::circle_capsule(that, this).map(ContactManifold::flip)
+        }
+        (Rectangle(this), Capsule(that)) => contacts::rectangle_capsule(this, that),
+        (Capsule(this), Rectangle(that)) => {
+            contacts::rectangle_capsule(that, this).map(ContactManifold::flip)
+        }
+        (Polygon(this), Capsule(that)) => contacts::polygon_capsule(this, that),
+        (Capsule(this), Polygon(that)) => {
+            contacts::polygon_capsule(that, this).map(ContactManifold::flip)
+        }
     }
 }
 
-mod contacts {
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_4;
+
     use super::*;
 
-    pub fn circle_circle(this: &Circle, that: &Circle) -> Contact {
-        let this_to_that = &that.body.position - &this.body.position;
-        let length = this_to_that.length();
+    fn create_rectangle(position: Vec2D, orientation: f64, half_extent: f64) -> Rectangle {
+        let mut body = BaseDynamicBody::new(position, ZERO, 1., 1.);
+        body.orientation = orientation;
+
+        Rectangle::new(body, half_extent, half_extent)
+    }
 
-        let distance = length - (this.radius + that.radius);
+    #[test]
+    fn test_rectangle_rectangle_uses_separating_axes_of_rotated_rectangles() {
+        let this = create_rectangle(ZERO, 0., 1.);
+        let that = create_rectangle(Vec2D { x: 2.2, y: 2.2 }, FRAC_PI_4, 1.);
 
-        Contact {
-            normal: &this_to_that / length,
-            distance,
-        }
+        // The bounding volumes overlap, but the rotated rectangle is not touching the corner.
+        assert!(
+            this.to_bounding_volume()
+                .is_intersecting(&that.to_bounding_volume())
+        );
+        assert!(contacts::rectangle_rectangle(&this, &that).is_none());
     }
 
-    pub fn rectangle_rectangle(this: &Rectangle, that: &Rectangle) -> Option<Contact> {
-        let displacement = &that.body.position - &this.body.position;
+    #[test]
+    fn test_rectangle_rectangle_contact_is_at_the_incident_corner() {
+        let this = create_rectangle(ZERO, 0., 1.);
+        let that = create_rectangle(Vec2D { x: 2.3, y: 0. }, FRAC_PI_4, 1.);
 
-        let x_overlap = this.half_width + that.half_width - displacement.x.abs();
-        let y_overlap = this.half_height + that.half_height - displacement.y.abs();
+        let manifold = contacts::rectangle_rectangle(&this, &that).unwrap();
 
-        if x_overlap <= 0. || y_overlap <= 0. {
-            return None;
-        }
+        assert_eq!(manifold.normal, UNIT_RIGHT);
+        assert!((manifold.deepest_point().distance - (2.3 - 1. - 2f64.sqrt())).abs() < 1e-9);
+        assert_eq!(manifold.points().len(), 1);
+        assert!(
+            (&manifold.points()[0].position
+                - &Vec2D {
+                    x: 2.3 - 2f64.sqrt(),
+                    y: 0.
+                })
+                .length()
+                < 1e-9
+        );
+    }
 
-        if x_overlap < y_overlap {
-            let normal = if displacement.x < 0. {
-                UNIT_LEFT
-            } else {
-                UNIT_RIGHT
-            };
+    #[test]
+    fn test_circle_rectangle_works_in_the_rotated_frame() {
+        let circle = Circle::new(BaseDynamicBody::new(ZERO, ZERO, 1., 1.), 1.);
+        let rectangle = create_rectangle(Vec2D { x: 2.3, y: 0. }, FRAC_PI_4, 1.);
 
-            Some(Contact {
-                normal,
-                distance: -x_overlap,
-            })
-        } else {
-            let normal = if displacement.y < 0. {
-                UNIT_UP
-            } else {
-                UNIT_DOWN
-            };
-
-            Some(Contact {
-                normal,
-                distance: -y_overlap,
-            })
-        }
+        let manifold = contacts::circle_rectangle(&circle, &rectangle).unwrap();
+
+        assert!((&manifold.normal - &UNIT_RIGHT).length() < 1e-9);
+        assert!((manifold.deepest_point().distance - (2.3 - 2f64.sqrt() - 1.)).abs() < 1e-9);
     }
 
-    pub fn circle_rectangle(this: &Circle, that: &Rectangle) -> Contact {
-        let displacement = &that.body.position - &this.body.position;
+    #[test]
+    fn test_line_rectangle_uses_rotated_corners() {
+        let line = Line {
+            normal: UNIT_UP,
+            origin_distance: 10.,
+        };
+        let rectangle = create_rectangle(Vec2D { x: 0., y: 9. }, FRAC_PI_4, 1.);
+
+        let manifold = contacts::line_rectangle(&line, &rectangle).unwrap();
 
-        let clamped_displacement = displacement.clamp(
-            &Vec2D {
-                x: -that.half_width,
-                y: -that.half_height,
-            },
-            &Vec2D {
-                x: that.half_width,
-                y: that.half_height,
-            },
+        assert_eq!(manifold.points().len(), 1);
+        assert!((manifold.deepest_point().distance - (1. - 2f64.sqrt())).abs() < 1e-9);
+        assert!(
+            (&manifold.points()[0].position
+                - &Vec2D {
+                    x: 0.,
+                    y: 9. + 2f64.sqrt()
+                })
+                .length()
+                < 1e-9
         );
+    }
 
-        let is_inside = clamped_displacement == displacement;
-
-        let closest_point = if is_inside {
-            if displacement.x.abs() > displacement.y.abs() {
-                Vec2D {
-                    x: clamped_displacement.x.signum() * that.half_width,
-                    y: clamped_displacement.y,
-                }
-            } else {
-                Vec2D {
-                    x: clamped_displacement.x,
-                    y: clamped_displacement.y.signum() * that.half_height,
-                }
-            }
-        } else {
-            clamped_displacement
+    #[test]
+    fn test_line_rectangle_has_two_points_when_resting_flat() {
+        let line = Line {
+            normal: UNIT_UP,
+            origin_distance: 10.,
         };
+        let rectangle = create_rectangle(Vec2D { x: 0., y: 9.5 }, 0., 1.);
 
-        let normal = &displacement - &closest_point;
-        let length = normal.length();
+        let manifold = contacts::line_rectangle(&line, &rectangle).unwrap();
 
-        let distance = length - this.radius;
+        let mut xs: Vec<_> = manifold
+            .points()
+            .iter()
+            .map(|point| {
+                assert!((point.distance + 0.5).abs() < 1e-9);
+                point.position.x
+            })
+            .collect();
+        xs.sort_by(f64::total_cmp);
 
-        if is_inside {
-            Contact {
-                normal: -(&normal / length),
-                distance,
-            }
-        } else {
-            Contact {
-                normal: &normal / length,
-                distance,
-            }
-        }
+        assert_eq!(xs, vec![-1., 1.]);
     }
 
-    pub fn line_circle(this: &Line, that: &Circle) -> Contact {
-        let distance =
-            this.normal.dot_product(&that.body.position) + this.origin_distance - that.radius;
+    fn create_triangle(position: Vec2D) -> Polygon {
+        let body = BaseDynamicBody::new(position, ZERO, 1., 1.);
 
-        Contact {
-            normal: this.normal,
-            distance,
+        Polygon::new(
+            body,
+            &[
+                Vec2D { x: 0., y: -1. },
+                Vec2D { x: 1., y: 1. },
+                Vec2D { x: -1., y: 1. },
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rectangle_polygon_clips_the_incident_edge() {
+        let rectangle = create_rectangle(ZERO, 0., 1.);
+        // The triangle's flat base rests on top of the rectangle.
+        let triangle = create_triangle(Vec2D { x: 0.5, y: -1.5 });
+        let base_y = triangle
+            .world_vertices()
+            .map(|vertex| vertex.y)
+            .fold(f64::MIN, f64::max);
+
+        let manifold = generate_contact_dynamic(
+            &DynamicBody::Polygon(triangle),
+            &DynamicBody::Rectangle(rectangle),
+        )
+        .unwrap();
+
+        assert!((&manifold.normal - &UNIT_DOWN).length() < 1e-9);
+        assert!((manifold.deepest_point().distance - (-1. - base_y)).abs() < 1e-9);
+        // The base spans from x = -0.5 to x = 1.5, clipped to the rectangle's top face.
+        let mut xs: Vec<_> = manifold
+            .points()
+            .iter()
+            .map(|point| point.position.x)
+            .collect();
+        xs.sort_by(f64::total_cmp);
+
+        assert_eq!(xs.len(), 2);
+        assert!((xs[0] + 0.5).abs() < 1e-9);
+        assert!((xs[1] - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_circle_polygon_uses_the_closest_vertex() {
+        let triangle = create_triangle(ZERO);
+        let apex = triangle
+            .world_vertices()
+            .min_by(|a, b| a.y.total_cmp(&b.y))
+            .unwrap();
+
+        let circle = Circle::new(
+            BaseDynamicBody::new(&apex + &Vec2D { x: 0., y: -0.5 }, ZERO, 1., 1.),
+            1.,
+        );
+
+        let manifold = contacts::circle_polygon(&circle, &triangle).unwrap();
+
+        assert!((&manifold.normal - &UNIT_DOWN).length() < 1e-9);
+        assert!((manifold.deepest_point().distance + 0.5).abs() < 1e-9);
+    }
+
+    fn create_capsule(position: Vec2D, orientation: f64) -> Capsule {
+        let mut body = BaseDynamicBody::new(position, ZERO, 1., 1.);
+        body.orientation = orientation;
+
+        Capsule::new(body, 2., 1.).unwrap()
+    }
+
+    #[test]
+    fn test_capsules_have_a_segment_at_their_core() {
+        let body = BaseDynamicBody::new(ZERO, ZERO, 1., 1.);
+
+        assert!(Capsule::new(body, 0., 1.).is_none());
+        assert!(Capsule::new(body, -1., 1.).is_none());
+        assert!(Capsule::new(body, f64::NAN, 1.).is_none());
+    }
+
+    #[test]
+    fn test_circle_capsule_uses_the_closest_point_on_the_segment() {
+        let circle = Circle::new(
+            BaseDynamicBody::new(Vec2D { x: 1., y: 1.5 }, ZERO, 1., 1.),
+            1.,
+        );
+        let capsule = create_capsule(ZERO, 0.);
+
+        let manifold = contacts::circle_capsule(&circle, &capsule).unwrap();
+
+        assert!((&manifold.normal - &UNIT_UP).length() < 1e-9);
+        assert!((manifold.deepest_point().distance + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_circle_capsule_in_line_with_the_segment() {
+        let capsule = create_capsule(ZERO, 0.);
+        let circle = |x| Circle::new(BaseDynamicBody::new(Vec2D { x, y: 0. }, ZERO, 1., 1.), 1.);
+
+        assert!(contacts::circle_capsule(&circle(10.), &capsule).is_none());
+
+        let manifold = contacts::circle_capsule(&circle(3.5), &capsule).unwrap();
+
+        assert!((&manifold.normal - &UNIT_LEFT).length() < 1e-9);
+        assert!((manifold.deepest_point().distance + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rectangle_capsule_touches_the_rounded_end() {
+        let rectangle = create_rectangle(ZERO, 0., 1.);
+        // The capsule's end is diagonally off the rectangle's corner.
+        let capsule = create_capsule(Vec2D { x: 3.5, y: 1.5 }, 0.);
+
+        let manifold = contacts::rectangle_capsule(&rectangle, &capsule).unwrap();
+
+        let expected_normal = &Vec2D { x: 1., y: 1. } / 2f64.sqrt();
+
+        assert!((&manifold.normal - &expected_normal).length() < 1e-9);
+        assert!((manifold.deepest_point().distance - (0.5 * 2f64.sqrt() - 1.)).abs() < 1e-9);
+
+        let far_capsule = create_capsule(Vec2D { x: 3.8, y: 1.8 }, 0.);
+
+        assert!(contacts::rectangle_capsule(&rectangle, &far_capsule).is_none());
+    }
+
+    #[test]
+    fn test_capsule_capsule_works_for_parallel_capsules() {
+        let this = create_capsule(ZERO, 0.);
+        let that = create_capsule(Vec2D { x: 1., y: 1.5 }, 0.);
+
+        let manifold = contacts::capsule_capsule(&this, &that).unwrap();
+
+        assert!((&manifold.normal - &UNIT_DOWN).length() < 1e-9);
+        assert_eq!(manifold.points().len(), 2);
+        assert!(
+            manifold
+                .points()
+                .iter()
+                .all(|point| (point.distance + 0.5).abs() < 1e-9)
+        );
+    }
+
+    #[test]
+    fn test_capsule_capsule_works_for_capsules_end_to_end() {
+        let this = create_capsule(ZERO, 0.);
+
+        assert!(
+            contacts::capsule_capsule(&this, &create_capsule(Vec2D { x: 6.5, y: 0. }, 0.))
+                .is_none()
+        );
+
+        for (x, distance) in [(4.5, -1.5), (4.1, -1.9)] {
+            let that = create_capsule(Vec2D { x, y: 0. }, 0.);
+            let manifold = contacts::capsule_capsule(&this, &that).unwrap();
+
+            assert!((&manifold.normal - &UNIT_RIGHT).length() < 1e-9);
+            assert_eq!(manifold.points().len(), 1);
+            assert!((manifold.deepest_point().distance - distance).abs() < 1e-9);
         }
     }
 
-    pub fn line_rectangle(this: &Line, that: &Rectangle) -> Contact {
-        let offsets = [
-            Vec2D {
-                x: that.half_width,
-                y: that.half_height,
-            },
-            Vec2D {
-                x: that.half_width,
-                y: -that.half_height,
-            },
-            Vec2D {
-                x: -that.half_width,
-                y: -that.half_height,
-            },
-            Vec2D {
-                x: -that.half_width,
-                y: that.half_height,
-            },
-        ];
-
-        let distances = offsets.into_iter().map(|offset| {
-            let point = &that.body.position + &offset;
-            this.normal.dot_product(&point) + this.origin_distance
-        });
-
-        // Safe because there are always 4 elements
-        let distance = distances.reduce(f64::min).unwrap();
-
-        Contact {
-            normal: this.normal,
-            distance,
+    #[test]
+    fn test_line_capsule_uses_the_lowest_end() {
+        let line = Line {
+            normal: UNIT_UP,
+            origin_distance: 10.,
+        };
+        let capsule = create_capsule(Vec2D { x: 0., y: 8. }, std::f64::consts::FRAC_PI_2);
+
+        let manifold = contacts::line_capsule(&line, &capsule).unwrap();
+
+        assert_eq!(manifold.points().len(), 1);
+        assert!((manifold.deepest_point().distance + 1.).abs() < 1e-9);
+        assert!((&manifold.points()[0].position - &Vec2D { x: 0., y: 11. }).length() < 1e-9);
+    }
+
+    /// The contacts between all the parts of a static body and a dynamic body, tagged with the
+    /// part that produced them.
+    fn generate_contacts_static(
+        this: &StaticBody,
+        that: &DynamicBody,
+    ) -> Vec<(usize, ContactManifold)> {
+        (0..this.part_count())
+            .filter_map(|part| Some((part, generate_contact_static(this, part, that)?)))
+            .collect()
+    }
+
+    fn create_circle(position: Vec2D) -> DynamicBody {
+        DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(position, ZERO, 1., 1.),
+            1.,
+        ))
+    }
+
+    #[test]
+    fn test_one_sided_segment_ignores_bodies_behind_it() {
+        let segment = |two_sided| {
+            StaticBody::Segment(
+                Segment::new(Vec2D { x: -10., y: 0. }, Vec2D { x: 10., y: 0. }, two_sided).unwrap(),
+            )
+        };
+        let below = create_circle(Vec2D { x: 0., y: 0.5 });
+
+        assert!(generate_contacts_static(&segment(false), &below).is_empty());
+
+        let contacts = generate_contacts_static(&segment(true), &below);
+
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].1.normal, UNIT_DOWN);
+    }
+
+    #[test]
+    fn test_segment_end_is_rounded() {
+        assert!(Segment::new(ZERO, ZERO, false).is_none());
+
+        let segment =
+            StaticBody::Segment(Segment::new(ZERO, Vec2D { x: 10., y: 0. }, false).unwrap());
+        let circle = create_circle(Vec2D { x: 10.5, y: -0.5 });
+
+        let contacts = generate_contacts_static(&segment, &circle);
+        let manifold = contacts[0].1;
+
+        let expected_normal = &Vec2D { x: 1., y: -1. } / 2f64.sqrt();
+
+        assert!((&manifold.normal - &expected_normal).length() < 1e-9);
+        assert!((manifold.deepest_point().distance - (0.5f64.sqrt() - 1.)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_segment_in_line_with_a_circle_touches_only_at_the_end() {
+        let segment =
+            StaticBody::Segment(Segment::new(ZERO, Vec2D { x: 10., y: 0. }, false).unwrap());
+
+        assert!(
+            generate_contacts_static(&segment, &create_circle(Vec2D { x: 30., y: 0. })).is_empty()
+        );
+
+        let contacts = generate_contacts_static(&segment, &create_circle(Vec2D { x: 10.5, y: 0. }));
+        let manifold = contacts[0].1;
+
+        assert!((&manifold.normal - &UNIT_RIGHT).length() < 1e-9);
+        assert!((manifold.deepest_point().distance + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_chain_does_not_catch_on_internal_vertices() {
+        let vertices = vec![Vec2D { x: -10., y: 0. }, ZERO, Vec2D { x: 10., y: 0. }];
+        let chain = Chain::new(vertices, false).unwrap();
+        let rectangle = create_rectangle(Vec2D { x: -0.97, y: -0.9 }, 0., 1.);
+
+        // On its own, the second segment pushes the rectangle sideways.
+        let second_segment = Segment::new(ZERO, Vec2D { x: 10., y: 0. }, false).unwrap();
+        let manifold = contacts::segment_rectangle(&second_segment, &rectangle).unwrap();
+        assert_eq!(manifold.normal, UNIT_LEFT);
+
+        let contacts = generate_contacts_static(
+            &StaticBody::Chain(chain),
+            &DynamicBody::Rectangle(rectangle),
+        );
+
+        assert!(!contacts.is_empty());
+
+        for (_, manifold) in contacts {
+            assert!((&manifold.normal - &UNIT_UP).length() < 1e-9);
         }
     }
+
+    #[test]
+    fn test_chain_convex_vertex_belongs_to_one_segment() {
+        let vertices = vec![Vec2D { x: -10., y: 10. }, ZERO, Vec2D { x: 10., y: 10. }];
+        let chain = StaticBody::Chain(Chain::new(vertices, false).unwrap());
+        let circle = create_circle(Vec2D { x: 0., y: -0.9 });
+
+        let contacts = generate_contacts_static(&chain, &circle);
+
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].0, 0);
+        assert!((&contacts[0].1.normal - &UNIT_UP).length() < 1e-9);
+    }
 }
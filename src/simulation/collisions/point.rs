@@ -0,0 +1,96 @@
+use super::*;
+
+/// Whether a part of a static body (see [`StaticBody::part_count`]) contains `point`. Segments
+/// and chains have no area, so never contain any point.
+pub fn contains_point_static(this: &StaticBody, _part: usize, point: &Vec2D) -> bool {
+    match this {
+        StaticBody::Line(this) => this.normal.dot_product(point) + this.origin_distance <= 0.,
+        StaticBody::Segment(_) | StaticBody::Chain(_) => false,
+        StaticBody::Circle(this) => shape_contains_point(Shape::Circle(this), point),
+        StaticBody::Rectangle(this) => shape_contains_point(Shape::Rectangle(this), point),
+        StaticBody::Polygon(this) => shape_contains_point(Shape::Polygon(this), point),
+    }
+}
+
+pub fn contains_point_dynamic(this: &DynamicBody, point: &Vec2D) -> bool {
+    shape_contains_point(this.into(), point)
+}
+
+fn shape_contains_point(this: Shape, point: &Vec2D) -> bool {
+    match this {
+        Shape::Circle(this) => (point - &this.body.position).length() <= this.radius,
+        Shape::Rectangle(this) => {
+            let local_point = (point - &this.body.position).rotate(-this.body.orientation);
+
+            local_point.x.abs() <= this.half_width && local_point.y.abs() <= this.half_height
+        }
+        Shape::Polygon(this) => {
+            let local_point = (point - &this.body.position).rotate(-this.body.orientation);
+
+            this.local_vertices()
+                .iter()
+                .zip(this.local_normals())
+                .all(|(vertex, normal)| normal.dot_product(&(&local_point - vertex)) <= 0.)
+        }
+        Shape::Capsule(this) => {
+            let [start, end] = this.endpoints();
+            let edge = &end - &start;
+
+            let fraction = if edge.length_squared() > 0. {
+                ((point - &start).dot_product(&edge) / edge.length_squared()).clamp(0., 1.)
+            } else {
+                0.
+            };
+
+            (point - &(&start + &(&edge * fraction))).length() <= this.radius
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contains_point_rotated_rectangle() {
+        let mut body = BaseDynamicBody::new(ZERO, ZERO, 0., 1.);
+        body.orientation = std::f64::consts::FRAC_PI_2;
+        let rectangle = DynamicBody::Rectangle(Rectangle::new(body, 10., 2.));
+
+        assert!(contains_point_dynamic(&rectangle, &Vec2D { x: 1., y: 9. }));
+        assert!(!contains_point_dynamic(&rectangle, &Vec2D { x: 9., y: 1. }));
+    }
+
+    #[test]
+    fn test_contains_point_polygon_and_capsule() {
+        let body = BaseDynamicBody::new(ZERO, ZERO, 0., 1.);
+        let triangle = DynamicBody::Polygon(
+            Polygon::new(
+                body,
+                &[
+                    Vec2D { x: 0., y: 0. },
+                    Vec2D { x: 10., y: 0. },
+                    Vec2D { x: 0., y: 10. },
+                ],
+            )
+            .unwrap(),
+        );
+        let capsule = DynamicBody::Capsule(Capsule::new(body, 10., 2.).unwrap());
+
+        assert!(contains_point_dynamic(&triangle, &Vec2D { x: 1., y: 1. }));
+        assert!(!contains_point_dynamic(&triangle, &Vec2D { x: 6., y: 6. }));
+        assert!(contains_point_dynamic(&capsule, &Vec2D { x: 11., y: 0. }));
+        assert!(!contains_point_dynamic(&capsule, &Vec2D { x: 0., y: 3. }));
+    }
+
+    #[test]
+    fn test_contains_point_line() {
+        let floor = StaticBody::Line(Line {
+            normal: UNIT_UP,
+            origin_distance: 10.,
+        });
+
+        assert!(contains_point_static(&floor, 0, &Vec2D { x: 0., y: 11. }));
+        assert!(!contains_point_static(&floor, 0, &Vec2D { x: 0., y: 9. }));
+    }
+}
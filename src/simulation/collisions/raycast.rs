@@ -0,0 +1,327 @@
+use crate::bounding_volume::BoundingVolume;
+
+use super::*;
+
+/// A half-line from `origin`, cut off after `max_distance`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vec2D,
+    /// Normalised.
+    pub direction: Vec2D,
+    pub max_distance: f64,
+}
+
+impl Ray {
+    /// Returns `None` if `direction` is zero.
+    pub fn new(origin: Vec2D, direction: Vec2D, max_distance: f64) -> Option<Self> {
+        let length = direction.length();
+
+        (length > 0.).then(|| Self {
+            origin,
+            direction: &direction / length,
+            max_distance,
+        })
+    }
+
+    pub fn point_at(&self, distance: f64) -> Vec2D {
+        &self.origin + &(&self.direction * distance)
+    }
+
+    pub fn end(&self) -> Vec2D {
+        self.point_at(self.max_distance)
+    }
+
+    pub fn to_bounding_volume(&self) -> BoundingVolume {
+        let end = self.end();
+
+        BoundingVolume {
+            top_left: self.origin.min(&end),
+            bottom_right: self.origin.max(&end),
+        }
+    }
+}
+
+/// Where a ray enters a body.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub distance: f64,
+    /// The outward normal of the body's surface at the hit point.
+    pub normal: Vec2D,
+}
+
+/// Casts a ray against a part of a static body (see [`StaticBody::part_count`]).
+///
+/// Rays starting inside a body do not hit it, and neither do rays hitting a one-sided segment
+/// from behind.
+pub fn raycast_static(this: &StaticBody, part: usize, ray: &Ray) -> Option<RayHit> {
+    match this {
+        StaticBody::Line(this) => raycast_line(this, ray),
+        StaticBody::Segment(this) => raycast_segment(this, ray),
+        StaticBody::Chain(this) => raycast_segment(&this.segment(part), ray),
+        StaticBody::Circle(this) => raycast_shape(Shape::Circle(this), ray),
+        StaticBody::Rectangle(this) => raycast_shape(Shape::Rectangle(this), ray),
+        StaticBody::Polygon(this) => raycast_shape(Shape::Polygon(this), ray),
+    }
+}
+
+/// Casts a ray against a dynamic body. Rays starting inside the body do not hit it.
+pub fn raycast_dynamic(this: &DynamicBody, ray: &Ray) -> Option<RayHit> {
+    raycast_shape(this.into(), ray)
+}
+
+fn raycast_shape(this: Shape, ray: &Ray) -> Option<RayHit> {
+    match this {
+        Shape::Circle(this) => raycast_circle(&this.body.position, this.radius, ray),
+        Shape::Rectangle(this) => raycast_convex(&this.corners(), &this.body.position, ray),
+        Shape::Polygon(this) => {
+            let mut vertices = [ZERO; MAX_POLYGON_VERTICES];
+            let vertex_count = this.local_vertices().len();
+
+            for (vertex, world_vertex) in vertices.iter_mut().zip(this.world_vertices()) {
+                *vertex = world_vertex;
+            }
+
+            raycast_convex(&vertices[..vertex_count], &this.body.position, ray)
+        }
+        Shape::Capsule(this) => raycast_capsule(this, ray),
+    }
+}
+
+fn raycast_line(this: &Line, ray: &Ray) -> Option<RayHit> {
+    let origin_distance = this.normal.dot_product(&ray.origin) + this.origin_distance;
+    let approach = this.normal.dot_product(&ray.direction);
+
+    // Behind the line (i.e. inside the solid half-plane), or moving away from it.
+    if origin_distance < 0. || approach >= 0. {
+        return None;
+    }
+
+    let distance = -origin_distance / approach;
+
+    (distance <= ray.max_distance).then_some(RayHit {
+        distance,
+        normal: this.normal,
+    })
+}
+
+fn raycast_segment(this: &Segment, ray: &Ray) -> Option<RayHit> {
+    let edge = &this.end() - &this.start();
+    let denominator = ray.direction.cross_product(&edge);
+
+    if denominator == 0. {
+        return None;
+    }
+
+    let to_start = &this.start() - &ray.origin;
+    let distance = to_start.cross_product(&edge) / denominator;
+    let fraction = to_start.cross_product(&ray.direction) / denominator;
+
+    if !(0. ..=ray.max_distance).contains(&distance) || !(0. ..=1.).contains(&fraction) {
+        return None;
+    }
+
+    let mut normal = this.normal();
+
+    if normal.dot_product(&ray.direction) > 0. {
+        if !this.two_sided {
+            return None;
+        }
+
+        normal = -normal;
+    }
+
+    Some(RayHit { distance, normal })
+}
+
+fn raycast_circle(centre: &Vec2D, radius: f64, ray: &Ray) -> Option<RayHit> {
+    let from_centre = &ray.origin - centre;
+
+    let b = from_centre.dot_product(&ray.direction);
+    let c = from_centre.length_squared() - radius * radius;
+
+    // Starting inside the circle, or moving away from it.
+    if c < 0. || b > 0. {
+        return None;
+    }
+
+    let discriminant = b * b - c;
+
+    if discriminant < 0. {
+        return None;
+    }
+
+    let distance = -b - discriminant.sqrt();
+
+    (distance <= ray.max_distance).then(|| RayHit {
+        distance,
+        normal: &(&ray.point_at(distance) - centre) / radius,
+    })
+}
+
+/// Clips the ray against each edge of a convex polygon, with `centre` anywhere inside it.
+fn raycast_convex(vertices: &[Vec2D], centre: &Vec2D, ray: &Ray) -> Option<RayHit> {
+    let mut entry: Option<RayHit> = None;
+    let mut exit_distance = ray.max_distance;
+
+    for (i, vertex) in vertices.iter().enumerate() {
+        let edge = &vertices[(i + 1) % vertices.len()] - vertex;
+        let mut normal = &Vec2D {
+            x: edge.y,
+            y: -edge.x,
+        } / edge.length();
+
+        if normal.dot_product(&(vertex - centre)) < 0. {
+            normal = -normal;
+        }
+
+        // How far the origin is inside this edge, and how fast the ray approaches it.
+        let depth = normal.dot_product(&(vertex - &ray.origin));
+        let approach = normal.dot_product(&ray.direction);
+
+        if approach == 0. {
+            if depth < 0. {
+                return None;
+            }
+
+            continue;
+        }
+
+        let distance = depth / approach;
+
+        if approach < 0. {
+            if entry.is_none_or(|entry| distance > entry.distance) {
+                entry = Some(RayHit { distance, normal });
+            }
+        } else {
+            exit_distance = exit_distance.min(distance);
+        }
+    }
+
+    // Without an entry, the ray starts inside the polygon.
+    entry.filter(|entry| (0. ..=exit_distance).contains(&entry.distance))
+}
+
+fn raycast_capsule(this: &Capsule, ray: &Ray) -> Option<RayHit> {
+    let [start, end] = this.endpoints();
+
+    let Some(segment) = Segment::new(start, end, true) else {
+        return raycast_circle(&start, this.radius, ray);
+    };
+
+    let edge = &end - &start;
+    let fraction =
+        ((&ray.origin - &start).dot_product(&edge) / edge.length_squared()).clamp(0., 1.);
+    let closest_point = &start + &(&edge * fraction);
+
+    // Starting inside the capsule.
+    if (&ray.origin - &closest_point).length() < this.radius {
+        return None;
+    }
+
+    let offset = &segment.normal() * this.radius;
+    let side = [
+        &start + &offset,
+        &end + &offset,
+        &end - &offset,
+        &start - &offset,
+    ];
+
+    [
+        raycast_circle(&start, this.radius, ray),
+        raycast_circle(&end, this.radius, ray),
+        raycast_convex(&side, &this.body.position, ray),
+    ]
+    .into_iter()
+    .flatten()
+    .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_ray(origin: Vec2D, direction: Vec2D) -> Ray {
+        Ray::new(origin, direction, 100.).unwrap()
+    }
+
+    #[test]
+    fn test_raycast_circle() {
+        let circle = DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(Vec2D { x: 50., y: 0. }, ZERO, 0., 1.),
+            10.,
+        ));
+
+        let hit = raycast_dynamic(&circle, &create_ray(ZERO, UNIT_RIGHT)).unwrap();
+
+        assert_eq!(hit.distance, 40.);
+        assert_eq!(hit.normal, UNIT_LEFT);
+        assert!(raycast_dynamic(&circle, &create_ray(ZERO, UNIT_LEFT)).is_none());
+        assert!(
+            raycast_dynamic(&circle, &create_ray(Vec2D { x: 50., y: 0. }, UNIT_RIGHT)).is_none()
+        );
+    }
+
+    #[test]
+    fn test_raycast_rotated_rectangle() {
+        let mut body = BaseDynamicBody::new(Vec2D { x: 0., y: 50. }, ZERO, 0., 1.);
+        body.orientation = std::f64::consts::FRAC_PI_4;
+        let rectangle = DynamicBody::Rectangle(Rectangle::new(body, 10., 10.));
+
+        let hit = raycast_dynamic(&rectangle, &create_ray(ZERO, UNIT_DOWN)).unwrap();
+
+        assert!((hit.distance - (50. - 10. * 2f64.sqrt())).abs() < 1e-9);
+        assert!((hit.normal.y + 2f64.sqrt() / 2.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_raycast_line() {
+        let floor = StaticBody::Line(Line {
+            normal: UNIT_UP,
+            origin_distance: 50.,
+        });
+
+        let hit = raycast_static(&floor, 0, &create_ray(ZERO, Vec2D { x: 1., y: 1. })).unwrap();
+
+        assert!((hit.distance - 50. * 2f64.sqrt()).abs() < 1e-9);
+        assert_eq!(hit.normal, UNIT_UP);
+        assert!(raycast_static(&floor, 0, &create_ray(ZERO, UNIT_UP)).is_none());
+    }
+
+    #[test]
+    fn test_raycast_capsule() {
+        let capsule = DynamicBody::Capsule(
+            Capsule::new(
+                BaseDynamicBody::new(Vec2D { x: 0., y: 50. }, ZERO, 0., 1.),
+                20.,
+                5.,
+            )
+            .unwrap(),
+        );
+
+        let side =
+            raycast_dynamic(&capsule, &create_ray(Vec2D { x: 10., y: 0. }, UNIT_DOWN)).unwrap();
+        let end =
+            raycast_dynamic(&capsule, &create_ray(Vec2D { x: 50., y: 50. }, UNIT_LEFT)).unwrap();
+
+        assert_eq!(side.distance, 45.);
+        assert_eq!(side.normal, UNIT_UP);
+        assert_eq!(end.distance, 25.);
+        assert_eq!(end.normal, UNIT_RIGHT);
+    }
+
+    #[test]
+    fn test_raycast_one_sided_segment() {
+        // The normal of this segment points up.
+        let segment = StaticBody::Segment(
+            Segment::new(Vec2D { x: -10., y: 50. }, Vec2D { x: 10., y: 50. }, false).unwrap(),
+        );
+
+        let hit = raycast_static(&segment, 0, &create_ray(ZERO, UNIT_DOWN)).unwrap();
+
+        assert_eq!(hit.distance, 50.);
+        assert_eq!(hit.normal, UNIT_UP);
+        assert!(
+            raycast_static(&segment, 0, &create_ray(Vec2D { x: 0., y: 100. }, UNIT_UP)).is_none()
+        );
+    }
+}
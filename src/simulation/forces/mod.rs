@@ -0,0 +1,196 @@
+use super::body_or_world;
+use super::world::DynamicBodyHandle;
+use crate::arena::Arena;
+use crate::body::*;
+use crate::vec2::*;
+
+/// Adds forces to bodies at the start of each tick, through their force accumulators (see
+/// [`BaseDynamicBody::force`]).
+#[derive(Debug, Clone, Copy)]
+pub enum ForceGenerator {
+    Spring(SpringForce),
+}
+
+/// A damped spring between two anchors, pushing or pulling them along the line between them.
+///
+/// Unlike a [`SpringJoint`](crate::simulation::joints::SpringJoint), the force is applied
+/// explicitly, so springs much stiffer than the inverse of the square of the tick duration make
+/// the bodies oscillate out of control.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpringForce {
+    /// `None` to attach the spring to the world, at the world point `this_anchor`.
+    pub this: Option<DynamicBodyHandle>,
+    /// In the local coordinates of `this`.
+    pub this_anchor: Vec2D,
+    pub that: DynamicBodyHandle,
+    pub that_anchor: Vec2D,
+    pub rest_length: f64,
+    /// The force per unit of stretch.
+    pub stiffness: f64,
+    /// The force per unit of stretching speed.
+    pub damping: f64,
+}
+
+impl ForceGenerator {
+    /// The bodies the generator acts on.
+    pub fn bodies(&self) -> impl Iterator<Item = DynamicBodyHandle> {
+        match self {
+            ForceGenerator::Spring(spring) => spring.this.into_iter().chain([spring.that]),
+        }
+    }
+
+    pub fn is_attached_to(&self, handle: DynamicBodyHandle) -> bool {
+        self.bodies().any(|body| body == handle)
+    }
+
+    /// Whether the bodies are all in `bodies`.
+    pub(crate) fn is_valid(&self, bodies: &Arena<DynamicBody>) -> bool {
+        self.bodies().all(|body| bodies.contains(body))
+    }
+
+    pub(crate) fn apply(&self, bodies: &mut Arena<DynamicBody>) {
+        match self {
+            ForceGenerator::Spring(spring) => spring.apply(bodies),
+        }
+    }
+}
+
+impl From<SpringForce> for ForceGenerator {
+    fn from(spring: SpringForce) -> Self {
+        ForceGenerator::Spring(spring)
+    }
+}
+
+impl SpringForce {
+    fn apply(&self, bodies: &mut Arena<DynamicBody>) {
+        let this = body_or_world(bodies, self.this);
+        let that = bodies[self.that].as_ref();
+
+        let this_arm = self.this_anchor.rotate(this.orientation);
+        let that_arm = self.that_anchor.rotate(that.orientation);
+
+        let separation = &(&that.position + &that_arm) - &(&this.position + &this_arm);
+        let length = separation.length();
+
+        if length == 0. {
+            return;
+        }
+
+        let direction = &separation / length;
+        let stretching_speed =
+            (&that.velocity_at(&that_arm) - &this.velocity_at(&this_arm)).dot_product(&direction);
+
+        let force = &direction
+            * (-self.stiffness * (length - self.rest_length) - self.damping * stretching_speed);
+
+        if let Some(i) = self.this {
+            bodies[i].as_mut().apply_force(&-&force, &this_arm);
+        }
+
+        bodies[self.that].as_mut().apply_force(&force, &that_arm);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::world::World;
+
+    fn create_ball(world: &mut World, position: Vec2D) -> DynamicBodyHandle {
+        world.add_dynamic(DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(position, ZERO, 0., 1.),
+            5.,
+        )))
+    }
+
+    #[test]
+    fn test_a_spring_pulls_two_bodies_together_symmetrically() {
+        let mut world = World::new(ZERO);
+
+        let this = create_ball(&mut world, ZERO);
+        let that = create_ball(&mut world, Vec2D { x: 100., y: 0. });
+
+        world
+            .add_force_generator(SpringForce {
+                this: Some(this),
+                this_anchor: ZERO,
+                that,
+                that_anchor: ZERO,
+                rest_length: 50.,
+                stiffness: 10.,
+                damping: 0.,
+            })
+            .unwrap();
+
+        world.tick(0.01);
+
+        let this_body = world.get(this).unwrap().as_ref();
+        let that_body = world.get(that).unwrap().as_ref();
+
+        // 10 * 50 for 0.01s.
+        assert!((this_body.velocity.x - 5.).abs() < 1e-9);
+        assert!((that_body.velocity.x + 5.).abs() < 1e-9);
+        assert_eq!(this_body.force, ZERO);
+    }
+
+    #[test]
+    fn test_a_spring_attached_to_a_removed_body_is_skipped() {
+        let mut world = World::new(ZERO);
+
+        let this = create_ball(&mut world, ZERO);
+        let that = create_ball(&mut world, Vec2D { x: 100., y: 0. });
+        let removed = create_ball(&mut world, Vec2D { x: 200., y: 0. });
+        world.remove(removed);
+
+        let spring = world
+            .add_force_generator(SpringForce {
+                this: Some(this),
+                this_anchor: ZERO,
+                that,
+                that_anchor: ZERO,
+                rest_length: 50.,
+                stiffness: 10.,
+                damping: 0.,
+            })
+            .unwrap();
+
+        let ForceGenerator::Spring(force) = world.force_generator_mut(spring).unwrap();
+        force.that = removed;
+
+        world.tick(0.01);
+
+        assert_eq!(world.get(this).unwrap().as_ref().velocity, ZERO);
+    }
+
+    #[test]
+    fn test_a_damped_spring_suspends_a_body_under_gravity() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let ball = create_ball(&mut world, Vec2D { x: 0., y: 50. });
+        let spring = world
+            .add_force_generator(SpringForce {
+                this: None,
+                this_anchor: ZERO,
+                that: ball,
+                that_anchor: ZERO,
+                rest_length: 50.,
+                stiffness: 20.,
+                damping: 4.,
+            })
+            .unwrap();
+
+        for _ in 0..1000 {
+            world.tick(0.01);
+        }
+
+        let body = world.get(ball).unwrap().as_ref();
+
+        // 100 / 20 below the rest length.
+        assert!((body.position.y - 55.).abs() < 0.1);
+        assert!(body.velocity.length() < 0.1);
+
+        world.remove(ball);
+
+        assert!(world.remove_force_generator(spring).is_none());
+    }
+}
@@ -0,0 +1,101 @@
+use super::*;
+
+/// Keeps two anchors at a fixed distance, like a massless rod.
+#[derive(Debug, Clone, Copy)]
+pub struct DistanceJoint {
+    pub bodies: JointBodies,
+    pub length: f64,
+    impulse: f64,
+}
+
+impl DistanceJoint {
+    pub fn new(bodies: JointBodies, length: f64) -> Self {
+        Self {
+            bodies,
+            length,
+            impulse: 0.,
+        }
+    }
+
+    pub(super) fn warm_start(&self, solver: &mut SolverBodies) {
+        let (direction, _) = direction(solver);
+
+        solver.apply_point_impulse(&(&direction * self.impulse));
+    }
+
+    pub(super) fn solve_velocities(&mut self, solver: &mut SolverBodies) {
+        let (direction, _) = direction(solver);
+        let inverse_mass = solver.effective_inverse_mass(&direction);
+
+        if inverse_mass == 0. {
+            return;
+        }
+
+        let impulse = -solver.relative_velocity().dot_product(&direction) / inverse_mass;
+        self.impulse += impulse;
+
+        solver.apply_point_impulse(&(&direction * impulse));
+    }
+
+    pub(super) fn correct_positions(&self, solver: &mut SolverBodies, factor: f64) {
+        let (direction, length) = direction(solver);
+        let inverse_mass = solver.effective_inverse_mass(&direction);
+
+        if inverse_mass == 0. {
+            return;
+        }
+
+        let impulse = -(length - self.length) * factor / inverse_mass;
+
+        solver.apply_position_point_impulse(&(&direction * impulse));
+    }
+}
+
+/// The unit vector from the first anchor to the second, and the distance between them. The
+/// direction is zero if the anchors coincide.
+pub(super) fn direction(solver: &SolverBodies) -> (Vec2D, f64) {
+    let separation = solver.separation();
+    let length = separation.length();
+
+    if length == 0. {
+        return (ZERO, 0.);
+    }
+
+    (&separation / length, length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::world::World;
+
+    #[test]
+    fn test_a_pendulum_keeps_its_length() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let bob = world.add_dynamic(DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(Vec2D { x: 100., y: 0. }, ZERO, 0., 1.),
+            5.,
+        )));
+        world
+            .add_joint(DistanceJoint::new(
+                JointBodies::to_world(ZERO, bob, ZERO),
+                100.,
+            ))
+            .unwrap();
+
+        let mut lowest: f64 = 0.;
+
+        for _ in 0..300 {
+            world.tick(0.01);
+
+            let position = world.get(bob).unwrap().as_ref().position;
+
+            assert!((position.length() - 100.).abs() < 1.);
+            lowest = lowest.max(position.y);
+        }
+
+        // It swung through the bottom.
+        assert!(lowest > 99.);
+    }
+}
@@ -0,0 +1,404 @@
+mod distance;
+mod prismatic;
+mod revolute;
+mod rope;
+mod spring;
+mod weld;
+
+pub use distance::*;
+pub use prismatic::*;
+pub use revolute::*;
+pub use rope::*;
+pub use spring::*;
+pub use weld::*;
+
+use super::body_or_world;
+use super::world::DynamicBodyHandle;
+use crate::arena::Arena;
+use crate::body::*;
+use crate::vec2::*;
+
+/// A constraint between two bodies, or between a body and a fixed point of the world.
+#[derive(Debug, Clone, Copy)]
+pub enum Joint {
+    Distance(DistanceJoint),
+    Spring(SpringJoint),
+    Revolute(RevoluteJoint),
+    Rope(RopeJoint),
+    Prismatic(PrismaticJoint),
+    Weld(WeldJoint),
+}
+
+/// What a joint connects. Each anchor is in its body's local coordinates, except that of a joint
+/// attached to the world, which is in world coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointBodies {
+    /// `None` if the joint is attached to the world.
+    pub this: Option<DynamicBodyHandle>,
+    pub this_anchor: Vec2D,
+    pub that: DynamicBodyHandle,
+    pub that_anchor: Vec2D,
+    /// Whether the two bodies still collide with each other, which they do not by default.
+    pub collide_connected: bool,
+}
+
+impl JointBodies {
+    pub fn new(
+        this: DynamicBodyHandle,
+        this_anchor: Vec2D,
+        that: DynamicBodyHandle,
+        that_anchor: Vec2D,
+    ) -> Self {
+        Self {
+            this: Some(this),
+            this_anchor,
+            that,
+            that_anchor,
+            collide_connected: false,
+        }
+    }
+
+    /// Attaches `that` to the world, at the world point `anchor`.
+    pub fn to_world(anchor: Vec2D, that: DynamicBodyHandle, that_anchor: Vec2D) -> Self {
+        Self {
+            this: None,
+            this_anchor: anchor,
+            that,
+            that_anchor,
+            collide_connected: false,
+        }
+    }
+
+    pub fn is_attached_to(&self, handle: DynamicBodyHandle) -> bool {
+        self.this == Some(handle) || self.that == handle
+    }
+
+    /// Whether the bodies are distinct and all in `bodies`.
+    pub(crate) fn are_valid(&self, bodies: &Arena<DynamicBody>) -> bool {
+        self.this
+            .is_none_or(|this| bodies.contains(this) && this != self.that)
+            && bodies.contains(self.that)
+    }
+}
+
+/// The range a joint may move in: angles for a [`RevoluteJoint`], translations along the axis for
+/// a [`PrismaticJoint`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointLimits {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Drives a joint at a target speed: angular for a [`RevoluteJoint`], along the axis for a
+/// [`PrismaticJoint`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointMotor {
+    pub speed: f64,
+    /// The largest torque (or force, for a [`PrismaticJoint`]) the motor may apply.
+    pub max_force: f64,
+}
+
+impl Joint {
+    pub fn bodies(&self) -> &JointBodies {
+        match self {
+            Joint::Distance(joint) => &joint.bodies,
+            Joint::Spring(joint) => &joint.bodies,
+            Joint::Revolute(joint) => &joint.bodies,
+            Joint::Rope(joint) => &joint.bodies,
+            Joint::Prismatic(joint) => &joint.bodies,
+            Joint::Weld(joint) => &joint.bodies,
+        }
+    }
+
+    /// Applies the impulses accumulated on the previous tick.
+    pub(crate) fn warm_start(&self, bodies: &mut Arena<DynamicBody>) {
+        self.solve(bodies, |joint, solver| match joint {
+            Joint::Distance(joint) => joint.warm_start(solver),
+            Joint::Spring(joint) => joint.warm_start(solver),
+            Joint::Revolute(joint) => joint.warm_start(solver),
+            Joint::Rope(joint) => joint.warm_start(solver),
+            Joint::Prismatic(joint) => joint.warm_start(solver),
+            Joint::Weld(joint) => joint.warm_start(solver),
+        });
+    }
+
+    pub(crate) fn solve_velocities(&mut self, bodies: &mut Arena<DynamicBody>, elapsed: f64) {
+        let joint_bodies = *self.bodies();
+        let mut solver = SolverBodies::load(&joint_bodies, bodies);
+
+        match self {
+            Joint::Distance(joint) => joint.solve_velocities(&mut solver),
+            Joint::Spring(joint) => joint.solve_velocities(&mut solver, elapsed),
+            Joint::Revolute(joint) => joint.solve_velocities(&mut solver, elapsed),
+            Joint::Rope(joint) => joint.solve_velocities(&mut solver, elapsed),
+            Joint::Prismatic(joint) => joint.solve_velocities(&mut solver, elapsed),
+            Joint::Weld(joint) => joint.solve_velocities(&mut solver),
+        }
+
+        solver.store(&joint_bodies, bodies);
+    }
+
+    /// Moves the bodies back towards satisfying the joint, by `factor` of the error.
+    pub(crate) fn correct_positions(&self, bodies: &mut Arena<DynamicBody>, factor: f64) {
+        self.solve(bodies, |joint, solver| match joint {
+            Joint::Distance(joint) => joint.correct_positions(solver, factor),
+            // Springs are meant to stretch.
+            Joint::Spring(_) => {}
+            Joint::Revolute(joint) => joint.correct_positions(solver, factor),
+            Joint::Rope(joint) => joint.correct_positions(solver, factor),
+            Joint::Prismatic(joint) => joint.correct_positions(solver, factor),
+            Joint::Weld(joint) => joint.correct_positions(solver, factor),
+        });
+    }
+
+    fn solve(&self, bodies: &mut Arena<DynamicBody>, f: impl FnOnce(&Self, &mut SolverBodies)) {
+        let mut solver = SolverBodies::load(self.bodies(), bodies);
+
+        f(self, &mut solver);
+
+        solver.store(self.bodies(), bodies);
+    }
+}
+
+impl From<DistanceJoint> for Joint {
+    fn from(joint: DistanceJoint) -> Self {
+        Joint::Distance(joint)
+    }
+}
+
+impl From<SpringJoint> for Joint {
+    fn from(joint: SpringJoint) -> Self {
+        Joint::Spring(joint)
+    }
+}
+
+impl From<RevoluteJoint> for Joint {
+    fn from(joint: RevoluteJoint) -> Self {
+        Joint::Revolute(joint)
+    }
+}
+
+impl From<RopeJoint> for Joint {
+    fn from(joint: RopeJoint) -> Self {
+        Joint::Rope(joint)
+    }
+}
+
+impl From<PrismaticJoint> for Joint {
+    fn from(joint: PrismaticJoint) -> Self {
+        Joint::Prismatic(joint)
+    }
+}
+
+impl From<WeldJoint> for Joint {
+    fn from(joint: WeldJoint) -> Self {
+        Joint::Weld(joint)
+    }
+}
+
+/// Copies of the two bodies of a joint, as seen by the solver.
+struct SolverBodies {
+    this: BaseDynamicBody,
+    this_anchor: Vec2D,
+    that: BaseDynamicBody,
+    that_anchor: Vec2D,
+}
+
+impl SolverBodies {
+    fn load(joint_bodies: &JointBodies, bodies: &Arena<DynamicBody>) -> Self {
+        Self {
+            this: body_or_world(bodies, joint_bodies.this),
+            this_anchor: joint_bodies.this_anchor,
+            that: *bodies[joint_bodies.that].as_ref(),
+            that_anchor: joint_bodies.that_anchor,
+        }
+    }
+
+    fn store(&self, joint_bodies: &JointBodies, bodies: &mut Arena<DynamicBody>) {
+        if let Some(i) = joint_bodies.this {
+            *bodies[i].as_mut() = self.this;
+        }
+
+        *bodies[joint_bodies.that].as_mut() = self.that;
+    }
+
+    /// The anchors relative to the centres of their bodies, in world orientation.
+    fn arms(&self) -> (Vec2D, Vec2D) {
+        (
+            self.this_anchor.rotate(self.this.orientation),
+            self.that_anchor.rotate(self.that.orientation),
+        )
+    }
+
+    /// From the first anchor to the second, in world coordinates.
+    fn separation(&self) -> Vec2D {
+        let (this_arm, that_arm) = self.arms();
+
+        &(&self.that.position + &that_arm) - &(&self.this.position + &this_arm)
+    }
+
+    /// The velocity of the second anchor relative to the first.
+    fn relative_velocity(&self) -> Vec2D {
+        let (this_arm, that_arm) = self.arms();
+
+        &self.that.velocity_at(&that_arm) - &self.this.velocity_at(&this_arm)
+    }
+
+    /// The orientation of the second body relative to the first.
+    fn angle(&self) -> f64 {
+        self.that.orientation - self.this.orientation
+    }
+
+    fn angular_inverse_mass(&self) -> f64 {
+        self.this.inverse_moment_of_inertia + self.that.inverse_moment_of_inertia
+    }
+
+    /// The inverse of the mass that the anchors appear to have when pulled apart along
+    /// `direction`.
+    fn effective_inverse_mass(&self, direction: &Vec2D) -> f64 {
+        let (this_arm, that_arm) = self.arms();
+        let this_factor = this_arm.cross_product(direction);
+        let that_factor = that_arm.cross_product(direction);
+
+        self.this.inverse_mass
+            + self.that.inverse_mass
+            + this_factor * this_factor * self.this.inverse_moment_of_inertia
+            + that_factor * that_factor * self.that.inverse_moment_of_inertia
+    }
+
+    /// The matrix turning an impulse at the anchors into their change in relative velocity.
+    fn point_mass_matrix(&self) -> [[f64; 2]; 2] {
+        let (this_arm, that_arm) = self.arms();
+        let (this_inertia, that_inertia) = (
+            self.this.inverse_moment_of_inertia,
+            self.that.inverse_moment_of_inertia,
+        );
+        let mass = self.this.inverse_mass + self.that.inverse_mass;
+
+        let k_xy = -this_arm.y * this_arm.x * this_inertia - that_arm.y * that_arm.x * that_inertia;
+
+        [
+            [
+                mass + this_arm.y * this_arm.y * this_inertia
+                    + that_arm.y * that_arm.y * that_inertia,
+                k_xy,
+            ],
+            [
+                k_xy,
+                mass + this_arm.x * this_arm.x * this_inertia
+                    + that_arm.x * that_arm.x * that_inertia,
+            ],
+        ]
+    }
+
+    /// Applies `linear` and the angular impulse `that_angular` to the second body, and their
+    /// opposites (with `this_angular`) to the first.
+    fn apply_impulse(&mut self, linear: &Vec2D, this_angular: f64, that_angular: f64) {
+        self.this.velocity -= &(linear * self.this.inverse_mass);
+        self.this.angular_velocity -= this_angular * self.this.inverse_moment_of_inertia;
+        self.that.velocity += &(linear * self.that.inverse_mass);
+        self.that.angular_velocity += that_angular * self.that.inverse_moment_of_inertia;
+    }
+
+    /// Like [`Self::apply_impulse`], but moving the bodies rather than changing their velocities.
+    fn apply_position_impulse(&mut self, linear: &Vec2D, this_angular: f64, that_angular: f64) {
+        self.this.position -= &(linear * self.this.inverse_mass);
+        self.this.orientation -= this_angular * self.this.inverse_moment_of_inertia;
+        self.that.position += &(linear * self.that.inverse_mass);
+        self.that.orientation += that_angular * self.that.inverse_moment_of_inertia;
+    }
+
+    /// Applies `impulse` at the anchors.
+    fn apply_point_impulse(&mut self, impulse: &Vec2D) {
+        let (this_arm, that_arm) = self.arms();
+
+        self.apply_impulse(
+            impulse,
+            this_arm.cross_product(impulse),
+            that_arm.cross_product(impulse),
+        );
+    }
+
+    fn apply_position_point_impulse(&mut self, impulse: &Vec2D) {
+        let (this_arm, that_arm) = self.arms();
+
+        self.apply_position_impulse(
+            impulse,
+            this_arm.cross_product(impulse),
+            that_arm.cross_product(impulse),
+        );
+    }
+}
+
+/// The velocity bias letting a body approach a limit `distance` away within the tick, so that
+/// limits only act once they would be reached.
+fn speculative_bias(distance: f64, elapsed: f64) -> f64 {
+    if distance > 0. && elapsed > 0. {
+        distance / elapsed
+    } else {
+        0.
+    }
+}
+
+/// Solves `k * x = b`, returning zero if `k` is singular.
+fn solve_2x2(k: &[[f64; 2]; 2], b: &Vec2D) -> Vec2D {
+    let determinant = k[0][0] * k[1][1] - k[0][1] * k[1][0];
+
+    if determinant == 0. {
+        return ZERO;
+    }
+
+    Vec2D {
+        x: (k[1][1] * b.x - k[0][1] * b.y) / determinant,
+        y: (k[0][0] * b.y - k[1][0] * b.x) / determinant,
+    }
+}
+
+/// Solves `k * x = b` by Cramer's rule, returning zero if `k` is singular.
+fn solve_3x3(k: &[[f64; 3]; 3], b: &[f64; 3]) -> [f64; 3] {
+    let determinant = |m: &[[f64; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+
+    let k_determinant = determinant(k);
+
+    if k_determinant == 0. {
+        return [0.; 3];
+    }
+
+    std::array::from_fn(|column| {
+        let mut m = *k;
+
+        for row in 0..3 {
+            m[row][column] = b[row];
+        }
+
+        determinant(&m) / k_determinant
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_matches_the_product() {
+        let k2 = [[4., 1.], [1., 3.]];
+        let x2 = solve_2x2(&k2, &Vec2D { x: 1., y: 2. });
+
+        assert!((4. * x2.x + x2.y - 1.).abs() < 1e-12);
+        assert!((x2.x + 3. * x2.y - 2.).abs() < 1e-12);
+
+        let k3 = [[4., 1., 0.], [1., 3., 1.], [0., 1., 2.]];
+        let x3 = solve_3x3(&k3, &[1., 2., 3.]);
+
+        for (row, b) in k3.iter().zip([1., 2., 3.]) {
+            let product: f64 = row.iter().zip(x3).map(|(k, x)| k * x).sum();
+            assert!((product - b).abs() < 1e-12);
+        }
+
+        assert_eq!(solve_2x2(&[[1., 1.], [1., 1.]], &UNIT_UP), ZERO);
+    }
+}
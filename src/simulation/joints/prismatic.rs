@@ -0,0 +1,325 @@
+use super::*;
+
+/// Lets `that` slide along an axis fixed to `this`, without rotating relative to it.
+#[derive(Debug, Clone, Copy)]
+pub struct PrismaticJoint {
+    pub bodies: JointBodies,
+    /// The direction to slide along, normalised, in the same coordinates as `this_anchor`.
+    pub axis: Vec2D,
+    /// The orientation of `that` relative to `this` that the joint keeps.
+    pub reference_angle: f64,
+    /// The translation of the second anchor along the axis, from the first.
+    pub limits: Option<JointLimits>,
+    pub motor: Option<JointMotor>,
+    /// The impulses across the axis and around it.
+    impulse: Vec2D,
+    motor_impulse: f64,
+    lower_impulse: f64,
+    upper_impulse: f64,
+}
+
+/// The axis and lever arms of a prismatic joint for the current positions of its bodies.
+struct Geometry {
+    axis: Vec2D,
+    perpendicular: Vec2D,
+    translation: f64,
+    /// The lever arms of an impulse along the axis, on each body.
+    axial_arms: (f64, f64),
+    perpendicular_arms: (f64, f64),
+}
+
+impl PrismaticJoint {
+    /// Returns `None` if `axis` is zero.
+    pub fn new(bodies: JointBodies, axis: Vec2D) -> Option<Self> {
+        let length = axis.length();
+
+        if length == 0. {
+            return None;
+        }
+
+        Some(Self {
+            bodies,
+            axis: &axis / length,
+            reference_angle: 0.,
+            limits: None,
+            motor: None,
+            impulse: ZERO,
+            motor_impulse: 0.,
+            lower_impulse: 0.,
+            upper_impulse: 0.,
+        })
+    }
+
+    fn geometry(&self, solver: &SolverBodies) -> Geometry {
+        let axis = self.axis.rotate(solver.this.orientation);
+        let perpendicular = Vec2D {
+            x: -axis.y,
+            y: axis.x,
+        };
+
+        let (this_arm, that_arm) = solver.arms();
+        let separation = solver.separation();
+        // An impulse on the first body acts at the second anchor, wherever it has slid to.
+        let this_arm = &separation + &this_arm;
+
+        Geometry {
+            translation: axis.dot_product(&separation),
+            axial_arms: (this_arm.cross_product(&axis), that_arm.cross_product(&axis)),
+            perpendicular_arms: (
+                this_arm.cross_product(&perpendicular),
+                that_arm.cross_product(&perpendicular),
+            ),
+            axis,
+            perpendicular,
+        }
+    }
+
+    fn axial_inverse_mass(solver: &SolverBodies, geometry: &Geometry) -> f64 {
+        let (this_arm, that_arm) = geometry.axial_arms;
+
+        solver.this.inverse_mass
+            + solver.that.inverse_mass
+            + this_arm * this_arm * solver.this.inverse_moment_of_inertia
+            + that_arm * that_arm * solver.that.inverse_moment_of_inertia
+    }
+
+    /// The matrix turning the impulses across and around the axis into the change in the
+    /// relative velocities across and around it.
+    fn mass_matrix(solver: &SolverBodies, geometry: &Geometry) -> [[f64; 2]; 2] {
+        let (this_arm, that_arm) = geometry.perpendicular_arms;
+        let (this_inertia, that_inertia) = (
+            solver.this.inverse_moment_of_inertia,
+            solver.that.inverse_moment_of_inertia,
+        );
+
+        let k_12 = this_inertia * this_arm + that_inertia * that_arm;
+        let k_22 = solver.angular_inverse_mass();
+
+        [
+            [
+                solver.this.inverse_mass
+                    + solver.that.inverse_mass
+                    + this_inertia * this_arm * this_arm
+                    + that_inertia * that_arm * that_arm,
+                k_12,
+            ],
+            // Without rotation, only the perpendicular impulse matters.
+            [k_12, if k_22 == 0. { 1. } else { k_22 }],
+        ]
+    }
+
+    fn apply_axial_impulse(solver: &mut SolverBodies, geometry: &Geometry, impulse: f64) {
+        let (this_arm, that_arm) = geometry.axial_arms;
+
+        solver.apply_impulse(
+            &(&geometry.axis * impulse),
+            impulse * this_arm,
+            impulse * that_arm,
+        );
+    }
+
+    fn apply_axial_position_impulse(solver: &mut SolverBodies, geometry: &Geometry, impulse: f64) {
+        let (this_arm, that_arm) = geometry.axial_arms;
+
+        solver.apply_position_impulse(
+            &(&geometry.axis * impulse),
+            impulse * this_arm,
+            impulse * that_arm,
+        );
+    }
+
+    fn axial_velocity(solver: &SolverBodies, geometry: &Geometry) -> f64 {
+        let (this_arm, that_arm) = geometry.axial_arms;
+
+        geometry
+            .axis
+            .dot_product(&(&solver.that.velocity - &solver.this.velocity))
+            + that_arm * solver.that.angular_velocity
+            - this_arm * solver.this.angular_velocity
+    }
+
+    pub(super) fn warm_start(&self, solver: &mut SolverBodies) {
+        let geometry = self.geometry(solver);
+        let (this_arm, that_arm) = geometry.perpendicular_arms;
+
+        Self::apply_axial_impulse(
+            solver,
+            &geometry,
+            self.motor_impulse + self.lower_impulse - self.upper_impulse,
+        );
+        solver.apply_impulse(
+            &(&geometry.perpendicular * self.impulse.x),
+            self.impulse.x * this_arm + self.impulse.y,
+            self.impulse.x * that_arm + self.impulse.y,
+        );
+    }
+
+    pub(super) fn solve_velocities(&mut self, solver: &mut SolverBodies, elapsed: f64) {
+        let geometry = self.geometry(solver);
+        let axial_inverse_mass = Self::axial_inverse_mass(solver, &geometry);
+
+        match self.motor {
+            Some(motor) if axial_inverse_mass > 0. => {
+                let velocity = Self::axial_velocity(solver, &geometry);
+                let impulse = (motor.speed - velocity) / axial_inverse_mass;
+                let max_impulse = motor.max_force * elapsed;
+                let previous = self.motor_impulse;
+
+                self.motor_impulse = (previous + impulse).clamp(-max_impulse, max_impulse);
+
+                Self::apply_axial_impulse(solver, &geometry, self.motor_impulse - previous);
+            }
+            _ => self.motor_impulse = 0.,
+        }
+
+        match self.limits {
+            Some(limits) if axial_inverse_mass > 0. => {
+                let bias = speculative_bias(geometry.translation - limits.lower, elapsed);
+                let velocity = Self::axial_velocity(solver, &geometry);
+                let impulse = -(velocity + bias) / axial_inverse_mass;
+                let previous = self.lower_impulse;
+
+                self.lower_impulse = (previous + impulse).max(0.);
+
+                Self::apply_axial_impulse(solver, &geometry, self.lower_impulse - previous);
+
+                let bias = speculative_bias(limits.upper - geometry.translation, elapsed);
+                let velocity = Self::axial_velocity(solver, &geometry);
+                let impulse = -(-velocity + bias) / axial_inverse_mass;
+                let previous = self.upper_impulse;
+
+                self.upper_impulse = (previous + impulse).max(0.);
+
+                Self::apply_axial_impulse(solver, &geometry, previous - self.upper_impulse);
+            }
+            _ => {
+                self.lower_impulse = 0.;
+                self.upper_impulse = 0.;
+            }
+        }
+
+        let (this_arm, that_arm) = geometry.perpendicular_arms;
+        let velocity = Vec2D {
+            x: geometry
+                .perpendicular
+                .dot_product(&(&solver.that.velocity - &solver.this.velocity))
+                + that_arm * solver.that.angular_velocity
+                - this_arm * solver.this.angular_velocity,
+            y: solver.that.angular_velocity - solver.this.angular_velocity,
+        };
+
+        let impulse = solve_2x2(&Self::mass_matrix(solver, &geometry), &-velocity);
+        self.impulse += &impulse;
+
+        solver.apply_impulse(
+            &(&geometry.perpendicular * impulse.x),
+            impulse.x * this_arm + impulse.y,
+            impulse.x * that_arm + impulse.y,
+        );
+    }
+
+    pub(super) fn correct_positions(&self, solver: &mut SolverBodies, factor: f64) {
+        let geometry = self.geometry(solver);
+        let (this_arm, that_arm) = geometry.perpendicular_arms;
+
+        let error = Vec2D {
+            x: geometry.perpendicular.dot_product(&solver.separation()),
+            y: solver.angle() - self.reference_angle,
+        };
+
+        let impulse = &solve_2x2(&Self::mass_matrix(solver, &geometry), &-error) * factor;
+
+        solver.apply_position_impulse(
+            &(&geometry.perpendicular * impulse.x),
+            impulse.x * this_arm + impulse.y,
+            impulse.x * that_arm + impulse.y,
+        );
+
+        if let Some(limits) = self.limits {
+            let geometry = self.geometry(solver);
+            let axial_inverse_mass = Self::axial_inverse_mass(solver, &geometry);
+            let error =
+                geometry.translation - geometry.translation.clamp(limits.lower, limits.upper);
+
+            if axial_inverse_mass > 0. {
+                Self::apply_axial_position_impulse(
+                    solver,
+                    &geometry,
+                    -error * factor / axial_inverse_mass,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::world::World;
+
+    #[test]
+    fn test_a_slider_falls_along_its_axis_until_its_limit() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let slider = world.add_dynamic(DynamicBody::Rectangle(Rectangle::new(
+            BaseDynamicBody::new(ZERO, ZERO, 0., 1.),
+            5.,
+            5.,
+        )));
+
+        // A slope down to the right, stopping 50 along it.
+        let mut joint = PrismaticJoint::new(
+            JointBodies::to_world(ZERO, slider, ZERO),
+            Vec2D { x: 1., y: 1. },
+        )
+        .unwrap();
+        joint.limits = Some(JointLimits {
+            lower: 0.,
+            upper: 50.,
+        });
+        world.add_joint(joint).unwrap();
+
+        for _ in 0..300 {
+            world.tick(0.01);
+
+            let body = world.get(slider).unwrap().as_ref();
+
+            assert!((body.position.x - body.position.y).abs() < 0.5);
+            assert!(body.orientation.abs() < 1e-3);
+        }
+
+        let position = world.get(slider).unwrap().as_ref().position;
+
+        assert!((position.length() - 50.).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_a_motor_drives_a_slider_against_gravity() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let lift = world.add_dynamic(DynamicBody::Rectangle(Rectangle::new(
+            BaseDynamicBody::new(ZERO, ZERO, 0., 1.),
+            5.,
+            5.,
+        )));
+
+        assert!(PrismaticJoint::new(JointBodies::to_world(ZERO, lift, ZERO), ZERO).is_none());
+
+        let mut joint =
+            PrismaticJoint::new(JointBodies::to_world(ZERO, lift, ZERO), UNIT_UP).unwrap();
+        joint.motor = Some(JointMotor {
+            speed: 10.,
+            max_force: 1000.,
+        });
+        world.add_joint(joint).unwrap();
+
+        for _ in 0..100 {
+            world.tick(0.01);
+        }
+
+        let body = world.get(lift).unwrap().as_ref();
+
+        assert!((body.velocity.y + 10.).abs() < 1e-6);
+        assert!(body.position.x.abs() < 1e-6);
+    }
+}
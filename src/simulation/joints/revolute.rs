@@ -0,0 +1,182 @@
+use super::*;
+
+/// Pins two anchors together, leaving the bodies free to rotate around them.
+#[derive(Debug, Clone, Copy)]
+pub struct RevoluteJoint {
+    pub bodies: JointBodies,
+    /// The orientation of `that` relative to `this` from which the joint's angle is measured.
+    pub reference_angle: f64,
+    pub limits: Option<JointLimits>,
+    pub motor: Option<JointMotor>,
+    impulse: Vec2D,
+    motor_impulse: f64,
+    lower_impulse: f64,
+    upper_impulse: f64,
+}
+
+impl RevoluteJoint {
+    pub fn new(bodies: JointBodies) -> Self {
+        Self {
+            bodies,
+            reference_angle: 0.,
+            limits: None,
+            motor: None,
+            impulse: ZERO,
+            motor_impulse: 0.,
+            lower_impulse: 0.,
+            upper_impulse: 0.,
+        }
+    }
+
+    fn angle(&self, solver: &SolverBodies) -> f64 {
+        solver.angle() - self.reference_angle
+    }
+
+    pub(super) fn warm_start(&self, solver: &mut SolverBodies) {
+        let angular = self.motor_impulse + self.lower_impulse - self.upper_impulse;
+
+        solver.apply_point_impulse(&self.impulse);
+        solver.apply_impulse(&ZERO, angular, angular);
+    }
+
+    pub(super) fn solve_velocities(&mut self, solver: &mut SolverBodies, elapsed: f64) {
+        let angular_inverse_mass = solver.angular_inverse_mass();
+        let angular_velocity =
+            |solver: &SolverBodies| solver.that.angular_velocity - solver.this.angular_velocity;
+
+        match self.motor {
+            Some(motor) if angular_inverse_mass > 0. => {
+                let impulse = -(angular_velocity(solver) - motor.speed) / angular_inverse_mass;
+                let max_impulse = motor.max_force * elapsed;
+                let previous = self.motor_impulse;
+
+                self.motor_impulse = (previous + impulse).clamp(-max_impulse, max_impulse);
+                let impulse = self.motor_impulse - previous;
+
+                solver.apply_impulse(&ZERO, impulse, impulse);
+            }
+            _ => self.motor_impulse = 0.,
+        }
+
+        match self.limits {
+            Some(limits) if angular_inverse_mass > 0. => {
+                let angle = self.angle(solver);
+
+                let bias = speculative_bias(angle - limits.lower, elapsed);
+                let impulse = -(angular_velocity(solver) + bias) / angular_inverse_mass;
+                let previous = self.lower_impulse;
+
+                self.lower_impulse = (previous + impulse).max(0.);
+                let impulse = self.lower_impulse - previous;
+
+                solver.apply_impulse(&ZERO, impulse, impulse);
+
+                let bias = speculative_bias(limits.upper - angle, elapsed);
+                let impulse = -(-angular_velocity(solver) + bias) / angular_inverse_mass;
+                let previous = self.upper_impulse;
+
+                self.upper_impulse = (previous + impulse).max(0.);
+                let impulse = self.upper_impulse - previous;
+
+                solver.apply_impulse(&ZERO, -impulse, -impulse);
+            }
+            _ => {
+                self.lower_impulse = 0.;
+                self.upper_impulse = 0.;
+            }
+        }
+
+        let impulse = solve_2x2(&solver.point_mass_matrix(), &-solver.relative_velocity());
+        self.impulse += &impulse;
+
+        solver.apply_point_impulse(&impulse);
+    }
+
+    pub(super) fn correct_positions(&self, solver: &mut SolverBodies, factor: f64) {
+        let angular_inverse_mass = solver.angular_inverse_mass();
+
+        if let Some(limits) = self.limits
+            && angular_inverse_mass > 0.
+        {
+            let angle = self.angle(solver);
+            let error = angle - angle.clamp(limits.lower, limits.upper);
+            let impulse = -error * factor / angular_inverse_mass;
+
+            solver.apply_position_impulse(&ZERO, impulse, impulse);
+        }
+
+        let impulse = solve_2x2(&solver.point_mass_matrix(), &-solver.separation());
+
+        solver.apply_position_point_impulse(&(&impulse * factor));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::world::World;
+
+    fn create_rod(world: &mut World) -> DynamicBodyHandle {
+        world.add_dynamic(DynamicBody::Rectangle(Rectangle::new(
+            BaseDynamicBody::new(Vec2D { x: 50., y: 0. }, ZERO, 0., 1.),
+            50.,
+            5.,
+        )))
+    }
+
+    #[test]
+    fn test_a_motor_spins_a_wheel_at_its_speed() {
+        let mut world = World::new(ZERO);
+
+        let wheel = world.add_dynamic(DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(ZERO, ZERO, 0., 1.),
+            10.,
+        )));
+
+        let mut joint = RevoluteJoint::new(JointBodies::to_world(ZERO, wheel, ZERO));
+        joint.motor = Some(JointMotor {
+            speed: 2.,
+            max_force: 1e6,
+        });
+        world.add_joint(joint).unwrap();
+
+        for _ in 0..10 {
+            world.tick(0.01);
+        }
+
+        let body = world.get(wheel).unwrap().as_ref();
+
+        assert!((body.angular_velocity - 2.).abs() < 1e-6);
+        assert!(body.position.length() < 1e-6);
+    }
+
+    #[test]
+    fn test_a_hinged_rod_stops_at_its_limits() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let rod = create_rod(&mut world);
+
+        let mut joint =
+            RevoluteJoint::new(JointBodies::to_world(ZERO, rod, Vec2D { x: -50., y: 0. }));
+        joint.limits = Some(JointLimits {
+            lower: -0.5,
+            upper: 0.5,
+        });
+        world.add_joint(joint).unwrap();
+
+        for _ in 0..300 {
+            world.tick(0.01);
+
+            let body = world.get(rod).unwrap().as_ref();
+            let hinge = &body.position + &Vec2D { x: -50., y: 0. }.rotate(body.orientation);
+
+            assert!(body.orientation < 0.55);
+            assert!(hinge.length() < 1.);
+        }
+
+        // It swung down onto its upper limit.
+        let body = world.get(rod).unwrap().as_ref();
+
+        assert!((body.orientation - 0.5).abs() < 0.05);
+    }
+}
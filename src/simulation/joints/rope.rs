@@ -0,0 +1,97 @@
+use super::distance::direction;
+use super::*;
+
+/// Keeps two anchors at most `max_length` apart, like a rope, leaving them free to come closer.
+#[derive(Debug, Clone, Copy)]
+pub struct RopeJoint {
+    pub bodies: JointBodies,
+    pub max_length: f64,
+    /// How hard the rope pulls the anchors together, which is never negative.
+    impulse: f64,
+}
+
+impl RopeJoint {
+    pub fn new(bodies: JointBodies, max_length: f64) -> Self {
+        Self {
+            bodies,
+            max_length,
+            impulse: 0.,
+        }
+    }
+
+    pub(super) fn warm_start(&self, solver: &mut SolverBodies) {
+        let (direction, _) = direction(solver);
+
+        solver.apply_point_impulse(&(&direction * -self.impulse));
+    }
+
+    pub(super) fn solve_velocities(&mut self, solver: &mut SolverBodies, elapsed: f64) {
+        let (direction, length) = direction(solver);
+        let inverse_mass = solver.effective_inverse_mass(&direction);
+
+        if inverse_mass == 0. {
+            return;
+        }
+
+        // Only pull once the anchors would get too far apart during the tick.
+        let bias = speculative_bias(self.max_length - length, elapsed);
+        let velocity = solver.relative_velocity().dot_product(&direction);
+        let impulse = (velocity - bias) / inverse_mass;
+        let previous = self.impulse;
+
+        self.impulse = (previous + impulse).max(0.);
+
+        solver.apply_point_impulse(&(&direction * (previous - self.impulse)));
+    }
+
+    pub(super) fn correct_positions(&self, solver: &mut SolverBodies, factor: f64) {
+        let (direction, length) = direction(solver);
+        let inverse_mass = solver.effective_inverse_mass(&direction);
+
+        if inverse_mass == 0. || length <= self.max_length {
+            return;
+        }
+
+        let impulse = -(length - self.max_length) * factor / inverse_mass;
+
+        solver.apply_position_point_impulse(&(&direction * impulse));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::world::World;
+
+    #[test]
+    fn test_a_rope_is_slack_until_it_is_taut() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let weight = world.add_dynamic(DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(Vec2D { x: 0., y: 50. }, ZERO, 0., 1.),
+            5.,
+        )));
+        world
+            .add_joint(RopeJoint::new(
+                JointBodies::to_world(ZERO, weight, ZERO),
+                100.,
+            ))
+            .unwrap();
+
+        // It falls freely while the rope is slack.
+        world.tick(0.1);
+
+        assert!((world.get(weight).unwrap().as_ref().velocity.y - 10.).abs() < 1e-9);
+
+        for _ in 0..300 {
+            world.tick(0.01);
+
+            assert!(world.get(weight).unwrap().as_ref().position.y < 101.);
+        }
+
+        let body = world.get(weight).unwrap().as_ref();
+
+        assert!((body.position.y - 100.).abs() < 1.);
+        assert!(body.velocity.length() < 5.);
+    }
+}
@@ -0,0 +1,90 @@
+use super::distance::direction;
+use super::*;
+
+/// Pulls two anchors towards a rest length like a damped spring, rather than holding them there.
+#[derive(Debug, Clone, Copy)]
+pub struct SpringJoint {
+    pub bodies: JointBodies,
+    pub rest_length: f64,
+    /// The force per unit of stretch.
+    pub stiffness: f64,
+    /// The force per unit of stretching speed.
+    pub damping: f64,
+    impulse: f64,
+}
+
+impl SpringJoint {
+    pub fn new(bodies: JointBodies, rest_length: f64, stiffness: f64, damping: f64) -> Self {
+        Self {
+            bodies,
+            rest_length,
+            stiffness,
+            damping,
+            impulse: 0.,
+        }
+    }
+
+    pub(super) fn warm_start(&self, solver: &mut SolverBodies) {
+        let (direction, _) = direction(solver);
+
+        solver.apply_point_impulse(&(&direction * self.impulse));
+    }
+
+    /// Solves the spring as a soft distance constraint, which stays stable however stiff it is.
+    pub(super) fn solve_velocities(&mut self, solver: &mut SolverBodies, elapsed: f64) {
+        let (direction, length) = direction(solver);
+        let inverse_mass = solver.effective_inverse_mass(&direction);
+        let softness = elapsed * (self.damping + elapsed * self.stiffness);
+
+        if inverse_mass == 0. || softness <= 0. {
+            return;
+        }
+
+        let gamma = 1. / softness;
+        let bias = (length - self.rest_length) * elapsed * self.stiffness * gamma;
+
+        let velocity = solver.relative_velocity().dot_product(&direction);
+        let impulse = -(velocity + bias + gamma * self.impulse) / (inverse_mass + gamma);
+        self.impulse += impulse;
+
+        solver.apply_point_impulse(&(&direction * impulse));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::world::World;
+
+    #[test]
+    fn test_a_damped_spring_settles_where_it_balances_gravity() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let weight = world.add_dynamic(DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(Vec2D { x: 0., y: 50. }, ZERO, 0., 1.),
+            5.,
+        )));
+        world
+            .add_joint(SpringJoint::new(
+                JointBodies::to_world(ZERO, weight, ZERO),
+                50.,
+                20.,
+                2.,
+            ))
+            .unwrap();
+
+        let mut lowest: f64 = 0.;
+
+        for _ in 0..1000 {
+            world.tick(0.01);
+            lowest = lowest.max(world.get(weight).unwrap().as_ref().position.y);
+        }
+
+        let body = world.get(weight).unwrap().as_ref();
+
+        // It overshot, then settled 100 / 20 below the rest length.
+        assert!(lowest > 56.);
+        assert!((body.position.y - 55.).abs() < 0.5);
+        assert!(body.velocity.length() < 1.);
+    }
+}
@@ -0,0 +1,142 @@
+use super::*;
+
+/// Glues two bodies together at an anchor, so that they neither move nor rotate relative to
+/// each other.
+#[derive(Debug, Clone, Copy)]
+pub struct WeldJoint {
+    pub bodies: JointBodies,
+    /// The orientation of `that` relative to `this` that the joint keeps.
+    pub reference_angle: f64,
+    /// The impulses along x and y, then around the anchor.
+    impulse: [f64; 3],
+}
+
+impl WeldJoint {
+    pub fn new(bodies: JointBodies) -> Self {
+        Self {
+            bodies,
+            reference_angle: 0.,
+            impulse: [0.; 3],
+        }
+    }
+
+    /// Solves for the impulse cancelling `error`, the relative motion of the anchors then the
+    /// relative rotation.
+    fn solve(solver: &SolverBodies, error: &[f64; 3]) -> [f64; 3] {
+        let (this_arm, that_arm) = solver.arms();
+        let [[k_11, k_12], [_, k_22]] = solver.point_mass_matrix();
+        let (this_inertia, that_inertia) = (
+            solver.this.inverse_moment_of_inertia,
+            solver.that.inverse_moment_of_inertia,
+        );
+
+        let k_13 = -this_arm.y * this_inertia - that_arm.y * that_inertia;
+        let k_23 = this_arm.x * this_inertia + that_arm.x * that_inertia;
+        let k_33 = solver.angular_inverse_mass();
+
+        // Neither body can rotate, so only the anchors need holding together.
+        if k_33 == 0. {
+            let impulse = solve_2x2(
+                &[[k_11, k_12], [k_12, k_22]],
+                &Vec2D {
+                    x: -error[0],
+                    y: -error[1],
+                },
+            );
+
+            return [impulse.x, impulse.y, 0.];
+        }
+
+        solve_3x3(
+            &[[k_11, k_12, k_13], [k_12, k_22, k_23], [k_13, k_23, k_33]],
+            &error.map(|error| -error),
+        )
+    }
+
+    fn apply_impulse(solver: &mut SolverBodies, [x, y, angular]: [f64; 3]) {
+        let (this_arm, that_arm) = solver.arms();
+        let impulse = Vec2D { x, y };
+
+        solver.apply_impulse(
+            &impulse,
+            this_arm.cross_product(&impulse) + angular,
+            that_arm.cross_product(&impulse) + angular,
+        );
+    }
+
+    pub(super) fn warm_start(&self, solver: &mut SolverBodies) {
+        Self::apply_impulse(solver, self.impulse);
+    }
+
+    pub(super) fn solve_velocities(&mut self, solver: &mut SolverBodies) {
+        let velocity = solver.relative_velocity();
+        let angular_velocity = solver.that.angular_velocity - solver.this.angular_velocity;
+
+        let impulse = Self::solve(solver, &[velocity.x, velocity.y, angular_velocity]);
+
+        for (total, impulse) in self.impulse.iter_mut().zip(impulse) {
+            *total += impulse;
+        }
+
+        Self::apply_impulse(solver, impulse);
+    }
+
+    pub(super) fn correct_positions(&self, solver: &mut SolverBodies, factor: f64) {
+        let separation = solver.separation();
+        let angle = solver.angle() - self.reference_angle;
+
+        let [x, y, angular] = Self::solve(solver, &[separation.x, separation.y, angle]);
+        let impulse = Vec2D { x, y };
+        let (this_arm, that_arm) = solver.arms();
+
+        solver.apply_position_impulse(
+            &(&impulse * factor),
+            (this_arm.cross_product(&impulse) + angular) * factor,
+            (that_arm.cross_product(&impulse) + angular) * factor,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::world::World;
+
+    #[test]
+    fn test_a_welded_box_holds_a_cantilever() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let base = world.add_dynamic(DynamicBody::Rectangle(Rectangle::new(
+            BaseDynamicBody::new(ZERO, ZERO, 0., 1.),
+            10.,
+            10.,
+        )));
+        let arm = world.add_dynamic(DynamicBody::Rectangle(Rectangle::new(
+            BaseDynamicBody::new(Vec2D { x: 30., y: 0. }, ZERO, 0., 1.),
+            20.,
+            5.,
+        )));
+
+        world
+            .add_joint(WeldJoint::new(JointBodies::to_world(ZERO, base, ZERO)))
+            .unwrap();
+        world
+            .add_joint(WeldJoint::new(JointBodies::new(
+                base,
+                Vec2D { x: 10., y: 0. },
+                arm,
+                Vec2D { x: -20., y: 0. },
+            )))
+            .unwrap();
+
+        for _ in 0..300 {
+            world.tick(0.01);
+        }
+
+        let arm = world.get(arm).unwrap().as_ref();
+
+        // It sags a little, but does not swing down.
+        assert!(arm.orientation.abs() < 0.05);
+        assert!((&arm.position - &Vec2D { x: 30., y: 0. }).length() < 1.);
+    }
+}
@@ -1,2 +1,20 @@
 pub mod collisions;
+pub mod forces;
+pub mod joints;
 pub mod world;
+
+use crate::arena::Arena;
+use crate::body::{BaseDynamicBody, DynamicBody};
+use crate::vec2::ZERO;
+use world::DynamicBodyHandle;
+
+/// A copy of the body `handle`, or if it is `None`, of the world that joints and force generators
+/// may be attached to instead: a body that nothing can move, at the origin.
+pub(crate) fn body_or_world(
+    bodies: &Arena<DynamicBody>,
+    handle: Option<DynamicBodyHandle>,
+) -> BaseDynamicBody {
+    handle.map_or(BaseDynamicBody::new(ZERO, ZERO, 0., 0.), |i| {
+        *bodies[i].as_ref()
+    })
+}
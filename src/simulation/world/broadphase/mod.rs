@@ -0,0 +1,264 @@
+mod spatial_hash;
+mod sweep_and_prune;
+mod tree;
+
+pub use spatial_hash::*;
+pub use sweep_and_prune::*;
+pub use tree::*;
+
+use std::fmt;
+
+use crate::bounding_volume::BoundingVolume;
+use crate::vec2::Vec2D;
+
+use super::DynamicBodyHandle;
+
+/// Finds the pairs of dynamic bodies that may be colliding, so that the exact (and expensive)
+/// contact generation only runs on those.
+///
+/// The [`World`](super::World) keeps its broadphase in sync with its dynamic bodies: every body is
+/// inserted when added, updated after each tick (and before it, if the body was changed in between)
+/// and removed when removed from the world.
+///
+/// It must be `Send` and `Sync`, so that worlds can be moved to other threads and queried from
+/// several at once.
+pub trait Broadphase: fmt::Debug + Send + Sync {
+    fn insert(&mut self, handle: DynamicBodyHandle, bounding_volume: &BoundingVolume);
+
+    fn remove(&mut self, handle: DynamicBodyHandle);
+
+    /// Called with the current bounding volume of a body, and how far it is expected to move during
+    /// the next tick.
+    fn update(
+        &mut self,
+        handle: DynamicBodyHandle,
+        bounding_volume: &BoundingVolume,
+        displacement: &Vec2D,
+    );
+
+    /// Calls `callback` once for every pair of bodies whose bounding volumes may intersect. It may
+    /// also report pairs that do not, but must not miss any pair that does.
+    fn for_each_pair(&mut self, callback: &mut dyn FnMut(DynamicBodyHandle, DynamicBodyHandle));
+
+    /// Calls `callback` with every body whose bounding volume may intersect `bounding_volume`.
+    fn query(&self, bounding_volume: &BoundingVolume, callback: &mut dyn FnMut(DynamicBodyHandle));
+
+    /// Calls `callback` with every body whose bounding volume may intersect the segment from
+    /// `start` to `end`. By default, this queries the bounding volume of the segment.
+    fn query_segment(
+        &self,
+        start: &Vec2D,
+        end: &Vec2D,
+        callback: &mut dyn FnMut(DynamicBodyHandle),
+    ) {
+        let bounding_volume = BoundingVolume {
+            top_left: start.min(end),
+            bottom_right: start.max(end),
+        };
+
+        self.query(&bounding_volume, callback);
+    }
+
+    fn clone_box(&self) -> Box<dyn Broadphase>;
+}
+
+impl Clone for Box<dyn Broadphase> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// The bounding volumes of the bodies, in a dense list that can be reordered, along with where
+/// each body is in it.
+#[derive(Debug, Clone, Default)]
+struct BoundingVolumeList {
+    entries: Vec<(DynamicBodyHandle, BoundingVolume)>,
+    /// The position of each body in `entries`, by handle index.
+    positions: Vec<usize>,
+}
+
+impl BoundingVolumeList {
+    fn insert(&mut self, handle: DynamicBodyHandle, bounding_volume: &BoundingVolume) {
+        if self.positions.len() <= handle.index() {
+            self.positions.resize(handle.index() + 1, 0);
+        }
+
+        self.positions[handle.index()] = self.entries.len();
+        self.entries.push((handle, *bounding_volume));
+    }
+
+    fn remove(&mut self, handle: DynamicBodyHandle) {
+        let position = self.positions[handle.index()];
+
+        self.entries.swap_remove(position);
+
+        if let Some((moved, _)) = self.entries.get(position) {
+            self.positions[moved.index()] = position;
+        }
+    }
+
+    fn update(&mut self, handle: DynamicBodyHandle, bounding_volume: &BoundingVolume) {
+        self.entries[self.positions[handle.index()]].1 = *bounding_volume;
+    }
+
+    fn get(&self, handle: DynamicBodyHandle) -> &BoundingVolume {
+        &self.entries[self.positions[handle.index()]].1
+    }
+
+    /// Updates the positions after the entries from `start` on have been reordered.
+    fn reindex(&mut self, start: usize) {
+        for (position, (handle, _)) in self.entries.iter().enumerate().skip(start) {
+            self.positions[handle.index()] = position;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arena::Arena;
+    use crate::body::*;
+    use crate::vec2::ZERO;
+
+    use super::*;
+
+    #[test]
+    fn test_broadphases_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<Box<dyn Broadphase>>();
+    }
+
+    fn create_square(x: f64, y: f64) -> BoundingVolume {
+        BoundingVolume {
+            top_left: Vec2D { x, y },
+            bottom_right: Vec2D {
+                x: x + 10.,
+                y: y + 10.,
+            },
+        }
+    }
+
+    fn find_pairs(broadphase: &mut dyn Broadphase) -> Vec<(usize, usize)> {
+        let mut pairs = vec![];
+
+        broadphase.for_each_pair(&mut |this, that| {
+            let (this, that) = (this.index(), that.index());
+            pairs.push((this.min(that), this.max(that)));
+        });
+
+        pairs.sort();
+        pairs
+    }
+
+    fn check_broadphase(mut broadphase: impl Broadphase) {
+        let mut bodies = Arena::new();
+        let mut handles = vec![];
+
+        for (x, y) in [(0., 0.), (5., 5.), (100., 0.), (105., 8.), (300., 300.)] {
+            let handle = bodies.insert(DynamicBody::Circle(Circle::new(
+                BaseDynamicBody::new(ZERO, ZERO, 0., 1.),
+                1.,
+            )));
+
+            broadphase.insert(handle, &create_square(x, y));
+            handles.push(handle);
+        }
+
+        assert_eq!(find_pairs(&mut broadphase), vec![(0, 1), (2, 3)]);
+
+        broadphase.remove(handles[1]);
+        broadphase.update(handles[4], &create_square(-5., 5.), &ZERO);
+
+        assert_eq!(find_pairs(&mut broadphase), vec![(0, 4), (2, 3)]);
+
+        let mut found = vec![];
+        broadphase.query(&create_square(97., 0.), &mut |handle| {
+            found.push(handle.index())
+        });
+        found.sort();
+
+        assert_eq!(found, vec![2, 3]);
+    }
+
+    /// Queries after bodies were moved, removed and added, without finding pairs in between.
+    fn check_queries(mut broadphase: impl Broadphase) {
+        let mut bodies = Arena::new();
+        let mut bounding_volumes = vec![];
+
+        // Scattered squares of various sizes.
+        let square = |i: usize, shift: f64| {
+            let size = (i % 7) as f64 * 4. + 1.;
+            let x = ((i * 37) % 101) as f64 * 3. + shift;
+            let y = ((i * 61) % 103) as f64 * 3.;
+
+            BoundingVolume {
+                top_left: Vec2D { x, y },
+                bottom_right: Vec2D {
+                    x: x + size,
+                    y: y + size,
+                },
+            }
+        };
+
+        for i in 0..60 {
+            let handle = bodies.insert(DynamicBody::Circle(Circle::new(
+                BaseDynamicBody::new(ZERO, ZERO, 0., 1.),
+                1.,
+            )));
+
+            broadphase.insert(handle, &square(i, 0.));
+            bounding_volumes.push(Some((handle, square(i, 0.))));
+        }
+
+        for (i, entry) in bounding_volumes.iter_mut().enumerate() {
+            let (handle, bounding_volume) = entry.as_mut().unwrap();
+
+            if i % 3 == 0 {
+                broadphase.remove(*handle);
+                *entry = None;
+            } else if i % 2 == 0 {
+                *bounding_volume = square(i, (i as f64 - 30.) * 5.);
+                broadphase.update(*handle, bounding_volume, &ZERO);
+            }
+        }
+
+        for query in (0..20)
+            .map(|i| square(i * 11, 2.))
+            .chain([square(3, -400.).union(&square(4, 400.))])
+        {
+            let mut found = vec![];
+            broadphase.query(&query, &mut |handle| found.push(handle.index()));
+            found.sort();
+
+            let expected: Vec<_> = bounding_volumes
+                .iter()
+                .flatten()
+                .filter(|(_, bounding_volume)| bounding_volume.is_intersecting(&query))
+                .map(|(handle, _)| handle.index())
+                .collect();
+
+            // The tree may also find bodies near the query, but never removed ones.
+            assert!(expected.iter().all(|index| found.contains(index)));
+            assert!(found.iter().all(|&index| bounding_volumes[index].is_some()));
+        }
+    }
+
+    #[test]
+    fn test_tree_broadphase_finds_overlapping_pairs() {
+        check_broadphase(TreeBroadphase::new());
+        check_queries(TreeBroadphase::new());
+    }
+
+    #[test]
+    fn test_sweep_and_prune_finds_overlapping_pairs() {
+        check_broadphase(SweepAndPrune::new());
+        check_queries(SweepAndPrune::new());
+    }
+
+    #[test]
+    fn test_spatial_hash_finds_overlapping_pairs() {
+        check_broadphase(SpatialHash::new(8.));
+        check_queries(SpatialHash::new(8.));
+        check_queries(SpatialHash::new(100.));
+    }
+}
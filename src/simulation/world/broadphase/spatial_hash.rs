@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, DefaultHasher};
+
+use crate::bounding_volume::BoundingVolume;
+use crate::vec2::Vec2D;
+
+use super::{BoundingVolumeList, Broadphase, DynamicBodyHandle};
+
+type Cell = (i64, i64);
+
+/// Splits space into a uniform grid of square cells, and only tests the bodies that share a cell.
+///
+/// Works best when the cells are about the size of the bodies, so suits piles of similarly sized
+/// bodies.
+#[derive(Debug, Clone)]
+pub struct SpatialHash {
+    cell_size: f64,
+    bounding_volumes: BoundingVolumeList,
+    /// The bodies overlapping each cell that any body overlaps. The hasher is not randomly
+    /// seeded, so that the pairs are always found in the same order.
+    cells: HashMap<Cell, Vec<DynamicBodyHandle>, BuildHasherDefault<DefaultHasher>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f64) -> Self {
+        Self {
+            cell_size,
+            bounding_volumes: BoundingVolumeList::default(),
+            cells: HashMap::default(),
+        }
+    }
+
+    fn cell(&self, point: &Vec2D) -> Cell {
+        (
+            (point.x / self.cell_size).floor() as i64,
+            (point.y / self.cell_size).floor() as i64,
+        )
+    }
+
+    /// The cells overlapped by `bounding_volume`, as those of its top left and bottom right
+    /// corners.
+    fn cell_range(&self, bounding_volume: &BoundingVolume) -> (Cell, Cell) {
+        (
+            self.cell(&bounding_volume.top_left),
+            self.cell(&bounding_volume.bottom_right),
+        )
+    }
+
+    fn add_to_cells(&mut self, handle: DynamicBodyHandle, bounding_volume: &BoundingVolume) {
+        let ((min_x, min_y), (max_x, max_y)) = self.cell_range(bounding_volume);
+
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                self.cells.entry((x, y)).or_default().push(handle);
+            }
+        }
+    }
+
+    fn remove_from_cells(&mut self, handle: DynamicBodyHandle, bounding_volume: &BoundingVolume) {
+        let ((min_x, min_y), (max_x, max_y)) = self.cell_range(bounding_volume);
+
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                let Some(handles) = self.cells.get_mut(&(x, y)) else {
+                    continue;
+                };
+
+                if let Some(i) = handles.iter().position(|&other| other == handle) {
+                    handles.swap_remove(i);
+                }
+
+                if handles.is_empty() {
+                    self.cells.remove(&(x, y));
+                }
+            }
+        }
+    }
+}
+
+impl Broadphase for SpatialHash {
+    fn insert(&mut self, handle: DynamicBodyHandle, bounding_volume: &BoundingVolume) {
+        self.bounding_volumes.insert(handle, bounding_volume);
+        self.add_to_cells(handle, bounding_volume);
+    }
+
+    fn remove(&mut self, handle: DynamicBodyHandle) {
+        let bounding_volume = *self.bounding_volumes.get(handle);
+
+        self.remove_from_cells(handle, &bounding_volume);
+        self.bounding_volumes.remove(handle);
+    }
+
+    fn update(
+        &mut self,
+        handle: DynamicBodyHandle,
+        bounding_volume: &BoundingVolume,
+        _displacement: &Vec2D,
+    ) {
+        let previous = *self.bounding_volumes.get(handle);
+
+        // Most bodies stay in the same cells from one tick to the next.
+        if self.cell_range(&previous) != self.cell_range(bounding_volume) {
+            self.remove_from_cells(handle, &previous);
+            self.add_to_cells(handle, bounding_volume);
+        }
+
+        self.bounding_volumes.update(handle, bounding_volume);
+    }
+
+    fn for_each_pair(&mut self, callback: &mut dyn FnMut(DynamicBodyHandle, DynamicBodyHandle)) {
+        for (&cell, handles) in &self.cells {
+            for (i, &this) in handles.iter().enumerate() {
+                let this_bounding_volume = self.bounding_volumes.get(this);
+
+                for &that in &handles[i + 1..] {
+                    let that_bounding_volume = self.bounding_volumes.get(that);
+
+                    if !this_bounding_volume.is_intersecting(that_bounding_volume) {
+                        continue;
+                    }
+
+                    // Bodies can share several cells, so only report them in the one containing
+                    // the top left corner of their intersection.
+                    let corner = this_bounding_volume
+                        .top_left
+                        .max(&that_bounding_volume.top_left);
+
+                    if self.cell(&corner) == cell {
+                        callback(this, that);
+                    }
+                }
+            }
+        }
+    }
+
+    fn query(&self, bounding_volume: &BoundingVolume, callback: &mut dyn FnMut(DynamicBodyHandle)) {
+        let mut visit = |cell: Cell, handles: &[DynamicBodyHandle]| {
+            for &handle in handles {
+                let entry_bounding_volume = self.bounding_volumes.get(handle);
+
+                // Likewise, only report each body in the cell containing the top left corner of
+                // its intersection with the query.
+                let corner = entry_bounding_volume
+                    .top_left
+                    .max(&bounding_volume.top_left);
+
+                if entry_bounding_volume.is_intersecting(bounding_volume)
+                    && self.cell(&corner) == cell
+                {
+                    callback(handle);
+                }
+            }
+        };
+
+        let ((min_x, min_y), (max_x, max_y)) = self.cell_range(bounding_volume);
+        let cell_count = (max_x as i128 - min_x as i128 + 1) * (max_y as i128 - min_y as i128 + 1);
+
+        // A query covering more cells than are in use only needs to visit those in use.
+        if cell_count > self.cells.len() as i128 {
+            for (&cell, handles) in &self.cells {
+                visit(cell, handles);
+            }
+        } else {
+            for x in min_x..=max_x {
+                for y in min_y..=max_y {
+                    if let Some(handles) = self.cells.get(&(x, y)) {
+                        visit((x, y), handles);
+                    }
+                }
+            }
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Broadphase> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,121 @@
+use crate::bounding_volume::BoundingVolume;
+use crate::vec2::Vec2D;
+
+use super::{BoundingVolumeList, Broadphase, DynamicBodyHandle};
+
+/// Keeps the bounding volumes sorted along the x axis, then sweeps through them, only testing the
+/// bodies whose x extents overlap.
+///
+/// Each update moves the body to its new place in the order, which is cheap while the bodies move
+/// little relative to each other. Queries binary search the order.
+#[derive(Debug, Clone, Default)]
+pub struct SweepAndPrune {
+    bounding_volumes: BoundingVolumeList,
+    /// At least the width of the widest bounding volume, so that queries know how far before
+    /// them to start looking.
+    max_width: f64,
+}
+
+impl SweepAndPrune {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the body at `position` to its place in the order, assuming that the others are in
+    /// order.
+    fn restore_order(&mut self, mut position: usize) {
+        let BoundingVolumeList { entries, positions } = &mut self.bounding_volumes;
+        let x = |entry: &(DynamicBodyHandle, BoundingVolume)| entry.1.top_left.x;
+
+        while position > 0 && x(&entries[position - 1]) > x(&entries[position]) {
+            entries.swap(position - 1, position);
+            positions[entries[position].0.index()] = position;
+            position -= 1;
+        }
+
+        while position + 1 < entries.len() && x(&entries[position + 1]) < x(&entries[position]) {
+            entries.swap(position, position + 1);
+            positions[entries[position].0.index()] = position;
+            position += 1;
+        }
+
+        positions[entries[position].0.index()] = position;
+    }
+
+    fn widen(&mut self, bounding_volume: &BoundingVolume) {
+        let width = bounding_volume.bottom_right.x - bounding_volume.top_left.x;
+
+        self.max_width = self.max_width.max(width);
+    }
+}
+
+impl Broadphase for SweepAndPrune {
+    fn insert(&mut self, handle: DynamicBodyHandle, bounding_volume: &BoundingVolume) {
+        self.bounding_volumes.insert(handle, bounding_volume);
+        self.widen(bounding_volume);
+        self.restore_order(self.bounding_volumes.entries.len() - 1);
+    }
+
+    fn remove(&mut self, handle: DynamicBodyHandle) {
+        let position = self.bounding_volumes.positions[handle.index()];
+
+        // Unlike swapping the last body in, this keeps the order.
+        self.bounding_volumes.entries.remove(position);
+        self.bounding_volumes.reindex(position);
+    }
+
+    fn update(
+        &mut self,
+        handle: DynamicBodyHandle,
+        bounding_volume: &BoundingVolume,
+        _displacement: &Vec2D,
+    ) {
+        self.bounding_volumes.update(handle, bounding_volume);
+        self.widen(bounding_volume);
+        self.restore_order(self.bounding_volumes.positions[handle.index()]);
+    }
+
+    fn for_each_pair(&mut self, callback: &mut dyn FnMut(DynamicBodyHandle, DynamicBodyHandle)) {
+        let entries = &self.bounding_volumes.entries;
+
+        for (i, (this, this_bounding_volume)) in entries.iter().enumerate() {
+            for (that, that_bounding_volume) in &entries[i + 1..] {
+                if that_bounding_volume.top_left.x >= this_bounding_volume.bottom_right.x {
+                    break;
+                }
+
+                if this_bounding_volume.is_intersecting(that_bounding_volume) {
+                    callback(*this, *that);
+                }
+            }
+        }
+
+        // Bodies that shrank or were removed may have left `max_width` too large.
+        self.max_width = entries
+            .iter()
+            .map(|(_, bounding_volume)| bounding_volume.bottom_right.x - bounding_volume.top_left.x)
+            .fold(0., f64::max);
+    }
+
+    fn query(&self, bounding_volume: &BoundingVolume, callback: &mut dyn FnMut(DynamicBodyHandle)) {
+        let entries = &self.bounding_volumes.entries;
+
+        // The bodies starting further left end before the query starts.
+        let start = entries.partition_point(|(_, entry_bounding_volume)| {
+            entry_bounding_volume.top_left.x <= bounding_volume.top_left.x - self.max_width
+        });
+        let end = entries.partition_point(|(_, entry_bounding_volume)| {
+            entry_bounding_volume.top_left.x < bounding_volume.bottom_right.x
+        });
+
+        for (handle, entry_bounding_volume) in &entries[start..end.max(start)] {
+            if entry_bounding_volume.is_intersecting(bounding_volume) {
+                callback(*handle);
+            }
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Broadphase> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,66 @@
+use crate::bounding_volume::{BoundingVolume, DynamicTree, ProxyId};
+use crate::vec2::Vec2D;
+
+use super::{Broadphase, DynamicBodyHandle};
+
+/// Keeps the fat bounding volumes of the bodies in a [`DynamicTree`]. Suits sparse worlds, and
+/// bodies of very different sizes.
+#[derive(Debug, Clone, Default)]
+pub struct TreeBroadphase {
+    tree: DynamicTree<DynamicBodyHandle>,
+    /// The proxy of each body in `tree`, by handle index.
+    proxies: Vec<ProxyId>,
+}
+
+impl TreeBroadphase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Broadphase for TreeBroadphase {
+    fn insert(&mut self, handle: DynamicBodyHandle, bounding_volume: &BoundingVolume) {
+        let proxy = self.tree.insert(bounding_volume, handle);
+
+        if self.proxies.len() <= handle.index() {
+            self.proxies.resize(handle.index() + 1, 0);
+        }
+
+        self.proxies[handle.index()] = proxy;
+    }
+
+    fn remove(&mut self, handle: DynamicBodyHandle) {
+        self.tree.remove(self.proxies[handle.index()]);
+    }
+
+    fn update(
+        &mut self,
+        handle: DynamicBodyHandle,
+        bounding_volume: &BoundingVolume,
+        displacement: &Vec2D,
+    ) {
+        self.tree
+            .move_proxy(self.proxies[handle.index()], bounding_volume, displacement);
+    }
+
+    fn for_each_pair(&mut self, callback: &mut dyn FnMut(DynamicBodyHandle, DynamicBodyHandle)) {
+        self.tree.for_each_pair(callback);
+    }
+
+    fn query(&self, bounding_volume: &BoundingVolume, callback: &mut dyn FnMut(DynamicBodyHandle)) {
+        self.tree.query(bounding_volume, callback);
+    }
+
+    fn query_segment(
+        &self,
+        start: &Vec2D,
+        end: &Vec2D,
+        callback: &mut dyn FnMut(DynamicBodyHandle),
+    ) {
+        self.tree.query_segment(start, end, callback);
+    }
+
+    fn clone_box(&self) -> Box<dyn Broadphase> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,148 @@
+use super::{DynamicBodyHandle, JointHandle, World};
+use crate::body::*;
+use crate::simulation::joints::*;
+use crate::vec2::*;
+
+/// Describes a chain of `link_count` circles spread evenly from `start` to `end`, with each link
+/// tied to the next by a [`RopeJoint`]. Neighbouring links do not collide, but the others do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChainSpec {
+    pub start: Vec2D,
+    pub end: Vec2D,
+    pub link_count: u32,
+    pub link_radius: f64,
+    pub link_mass: f64,
+    /// How much longer each rope is than the initial spacing of the links, as a fraction of it.
+    /// 0 gives a chain that cannot stretch beyond its initial length.
+    pub slack: f64,
+    /// Whether to pin the first link to the world at `start`, with a [`RevoluteJoint`].
+    pub pin_start: bool,
+    /// Whether to pin the last link to the world at `end`.
+    pub pin_end: bool,
+}
+
+impl Default for ChainSpec {
+    fn default() -> Self {
+        Self {
+            start: ZERO,
+            end: Vec2D { x: 100., y: 0. },
+            link_count: 10,
+            link_radius: 3.,
+            link_mass: 0.1,
+            slack: 0.,
+            pin_start: true,
+            pin_end: false,
+        }
+    }
+}
+
+/// The bodies and joints of a chain added by [`World::add_chain`], from start to end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainHandles {
+    pub links: Vec<DynamicBodyHandle>,
+    /// The ropes between the links, then the pins.
+    pub joints: Vec<JointHandle>,
+}
+
+impl World {
+    pub fn add_chain(&mut self, spec: &ChainSpec) -> ChainHandles {
+        let offset = &spec.end - &spec.start;
+        // A single link sits at `start`.
+        let gaps = spec.link_count.saturating_sub(1).max(1) as f64;
+        let spacing = offset.length() / gaps;
+
+        let links: Vec<_> = (0..spec.link_count)
+            .map(|i| {
+                let fraction = i as f64 / gaps;
+                let position = &spec.start + &(&offset * fraction);
+
+                self.add_dynamic(DynamicBody::Circle(Circle::new(
+                    BaseDynamicBody::new(position, ZERO, 0., 1. / spec.link_mass),
+                    spec.link_radius,
+                )))
+            })
+            .collect();
+
+        let mut joints: Vec<_> = links
+            .windows(2)
+            .map(|pair| {
+                let rope = RopeJoint::new(
+                    JointBodies::new(pair[0], ZERO, pair[1], ZERO),
+                    spacing * (1. + spec.slack),
+                );
+
+                self.add_joint(rope).expect("the links were just added")
+            })
+            .collect();
+
+        let pins = [
+            (spec.pin_start, links.first(), spec.start),
+            (spec.pin_end, links.last(), spec.end),
+        ];
+
+        for (is_pinned, link, anchor) in pins {
+            if let (true, Some(&link)) = (is_pinned, link) {
+                let pin = RevoluteJoint::new(JointBodies::to_world(anchor, link, ZERO));
+
+                joints.push(self.add_joint(pin).expect("the links were just added"));
+            }
+        }
+
+        ChainHandles { links, joints }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_a_chain_hangs_from_its_pins_without_stretching() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let spec = ChainSpec {
+            link_count: 11,
+            slack: 0.2,
+            pin_end: true,
+            ..ChainSpec::default()
+        };
+        let chain = world.add_chain(&spec);
+
+        assert_eq!(chain.links.len(), 11);
+        assert_eq!(chain.joints.len(), 12);
+
+        for _ in 0..500 {
+            world.tick(0.01);
+        }
+
+        let positions: Vec<_> = chain
+            .links
+            .iter()
+            .map(|&link| world.get(link).unwrap().as_ref().position)
+            .collect();
+
+        assert!((&positions[0] - &spec.start).length() < 0.5);
+        assert!((&positions[10] - &spec.end).length() < 0.5);
+
+        // It sags in the middle, with each rope at most a little over its length.
+        assert!(positions[5].y > 10.);
+
+        for pair in positions.windows(2) {
+            assert!((&pair[1] - &pair[0]).length() < 12.5);
+        }
+    }
+
+    #[test]
+    fn test_a_single_link_chain_has_no_ropes() {
+        let mut world = World::new(ZERO);
+
+        let chain = world.add_chain(&ChainSpec {
+            link_count: 1,
+            ..ChainSpec::default()
+        });
+
+        assert_eq!(chain.links.len(), 1);
+        assert_eq!(chain.joints.len(), 1);
+        assert_eq!(world.get(chain.links[0]).unwrap().as_ref().position, ZERO);
+    }
+}
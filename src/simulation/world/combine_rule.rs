@@ -0,0 +1,20 @@
+/// How to combine the coefficients (e.g. of friction) of two bodies in contact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CombineRule {
+    Min,
+    Max,
+    #[default]
+    Average,
+    Multiply,
+}
+
+impl CombineRule {
+    pub fn combine(self, this: f64, that: f64) -> f64 {
+        match self {
+            CombineRule::Min => this.min(that),
+            CombineRule::Max => this.max(that),
+            CombineRule::Average => (this + that) / 2.,
+            CombineRule::Multiply => this * that,
+        }
+    }
+}
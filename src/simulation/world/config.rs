@@ -0,0 +1,38 @@
+use super::CombineRule;
+
+/// The tuning knobs of the contact solver.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldConfig {
+    /// How many times per tick the solver iterates over all contacts to resolve velocities.
+    pub velocity_iterations: usize,
+    /// How many times per tick the solver iterates over all contacts to push bodies apart.
+    pub position_iterations: usize,
+    /// How deep bodies may overlap before they are pushed apart, to avoid jitter.
+    pub slop: f64,
+    /// The fraction of the remaining overlap that is corrected on each position iteration.
+    pub baumgarte_factor: f64,
+    pub restitution_combine_rule: CombineRule,
+    pub friction_combine_rule: CombineRule,
+    /// Below this approach speed, collisions are treated as resting contact and do not bounce,
+    /// so that resting bodies do not jitter.
+    pub restitution_velocity_threshold: f64,
+    /// Bodies that move further than this fraction of their inner radius in a tick get
+    /// continuous collision detection against static bodies, so that they do not tunnel through
+    /// them. Set to infinity to only use it for bullets.
+    pub continuous_collision_threshold: f64,
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self {
+            velocity_iterations: 10,
+            position_iterations: 1,
+            slop: 0.05,
+            baumgarte_factor: 0.4,
+            restitution_combine_rule: CombineRule::Min,
+            friction_combine_rule: CombineRule::Average,
+            restitution_velocity_threshold: 5.,
+            continuous_collision_threshold: 0.5,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::body::DynamicBody;
+
+/// The most samples taken along the motion. Beyond that, e.g. for a body moving at an extreme
+/// velocity, the samples are further apart than `step`.
+const MAX_STEPS: usize = 256;
+
+/// The most times the first overlapping sample is bisected, which is enough to reach the
+/// precision of an `f64` even if the distance is too large to get within the tolerance.
+const MAX_BISECTIONS: usize = 64;
+
+/// The body as it will be after moving for `elapsed` with its current velocities.
+pub fn advance(body: &DynamicBody, elapsed: f64) -> DynamicBody {
+    let mut body = *body;
+    body.as_mut().integrate(elapsed);
+    body
+}
+
+/// Finds the first time, as a fraction of the tick, at which `is_overlapping` becomes true.
+///
+/// The motion is sampled at most `step` apart, where `distance` is how far the bodies move
+/// relative to each other during the tick, so that a body does not skip over anything thicker
+/// than `step`. The number of samples is capped at [`MAX_STEPS`] though, so that an extreme (or
+/// infinite) distance cannot stall the tick. The first overlapping sample is then refined by
+/// bisection, until the bodies overlap by at most about `tolerance`.
+///
+/// Returns `None` if the bodies overlap from the start, as the discrete contacts deal with those.
+pub fn time_of_impact(
+    distance: f64,
+    step: f64,
+    tolerance: f64,
+    is_overlapping: impl Fn(f64) -> bool,
+) -> Option<f64> {
+    if is_overlapping(0.) {
+        return None;
+    }
+
+    let step_count = ((distance / step).ceil().max(1.) as usize).min(MAX_STEPS);
+    let mut previous_time = 0.;
+
+    for i in 1..=step_count {
+        let time = i as f64 / step_count as f64;
+
+        if is_overlapping(time) {
+            let (mut before, mut after) = (previous_time, time);
+
+            for _ in 0..MAX_BISECTIONS {
+                if (after - before) * distance <= tolerance {
+                    break;
+                }
+
+                let middle = (before + after) / 2.;
+
+                if is_overlapping(middle) {
+                    after = middle;
+                } else {
+                    before = middle;
+                }
+            }
+
+            return Some(after);
+        }
+
+        previous_time = time;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_of_impact_finds_thin_obstacles() {
+        // A point moving by 100 through an obstacle between 60.2 and 60.7.
+        let is_overlapping = |time: f64| (60.2..60.7).contains(&(100. * time));
+
+        let time = time_of_impact(100., 0.25, 0.01, is_overlapping).unwrap();
+
+        assert!((60.2..60.21).contains(&(100. * time)));
+        assert_eq!(time_of_impact(100., 1., 0.01, is_overlapping), None);
+        assert_eq!(time_of_impact(100., 0.25, 0.01, |_| true), None);
+    }
+
+    #[test]
+    fn test_time_of_impact_terminates_for_extreme_distances() {
+        for distance in [1e300, f64::INFINITY, f64::NAN] {
+            let time = time_of_impact(distance, 0.25, 0.01, |time| time >= 0.5).unwrap();
+
+            assert!((0.5..0.51).contains(&time));
+        }
+    }
+}
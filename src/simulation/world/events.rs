@@ -0,0 +1,165 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{BodyHandle, DynamicBodyHandle};
+use crate::simulation::collisions::ContactManifold;
+use crate::vec2::Vec2D;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionEventKind {
+    /// The bodies started touching during the tick.
+    Begin,
+    /// The bodies were already touching on the previous tick, and still are.
+    Persist,
+    /// The bodies stopped touching, or one of them was removed.
+    End,
+}
+
+/// A change in the contact between a body (or a part of a static body) and a dynamic body,
+/// reported by [`World::tick`](super::World::tick).
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionEvent {
+    pub kind: CollisionEventKind,
+    pub this: BodyHandle,
+    /// The part of `this`, e.g. the segment of a chain.
+    pub part: usize,
+    pub that: DynamicBodyHandle,
+    /// The contact, with its normal pointing from `this` to `that`. For ended contacts, this is
+    /// the last one seen.
+    pub manifold: ContactManifold,
+    /// The total impulse applied to `that` during the tick, `this` getting the opposite one. Zero
+    /// for ended contacts.
+    pub impulse: Vec2D,
+}
+
+impl CollisionEvent {
+    /// How deep the bodies overlap, at the deepest contact point.
+    pub fn penetration(&self) -> f64 {
+        -self.manifold.deepest_point().distance
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct ContactPair {
+    this: BodyHandle,
+    part: usize,
+    that: DynamicBodyHandle,
+}
+
+/// Remembers which bodies are touching from one tick to the next, to report the changes.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ContactTracker {
+    /// Ordered, so that the ended contacts are always reported in the same order.
+    touching: BTreeMap<ContactPair, ContactManifold>,
+    events: Vec<CollisionEvent>,
+}
+
+impl ContactTracker {
+    pub fn events(&self) -> &[CollisionEvent] {
+        &self.events
+    }
+
+    /// Replaces the touching bodies with those of `contacts`, each given with its manifold and
+    /// impulse, and records the events of the tick.
+    pub fn update(
+        &mut self,
+        contacts: impl IntoIterator<
+            Item = (BodyHandle, usize, DynamicBodyHandle, ContactManifold, Vec2D),
+        >,
+    ) {
+        self.events.clear();
+
+        let mut touching = BTreeMap::new();
+
+        for (this, part, that, manifold, impulse) in contacts {
+            let pair = ContactPair { this, part, that };
+
+            let kind = if self.touching.remove(&pair).is_some() {
+                CollisionEventKind::Persist
+            } else {
+                CollisionEventKind::Begin
+            };
+
+            self.events.push(CollisionEvent {
+                kind,
+                this,
+                part,
+                that,
+                manifold,
+                impulse,
+            });
+
+            touching.insert(pair, manifold);
+        }
+
+        // What is left is no longer touching.
+        for (ContactPair { this, part, that }, manifold) in std::mem::take(&mut self.touching) {
+            self.events.push(CollisionEvent {
+                kind: CollisionEventKind::End,
+                this,
+                part,
+                that,
+                manifold,
+                impulse: Vec2D::default(),
+            });
+        }
+
+        self.touching = touching;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorEventKind {
+    /// The body started overlapping the sensor during the tick.
+    Enter,
+    /// The body stopped overlapping the sensor, or one of them was removed.
+    Exit,
+}
+
+/// A body entering or leaving a sensor, reported by [`World::tick`](super::World::tick). Sensors
+/// do not detect each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SensorEvent {
+    pub kind: SensorEventKind,
+    pub sensor: BodyHandle,
+    pub other: BodyHandle,
+}
+
+/// Remembers which bodies overlap which sensors from one tick to the next, to report the changes.
+#[derive(Debug, Clone, Default)]
+pub(super) struct SensorTracker {
+    /// Pairs of a sensor and a body overlapping it, whatever parts of them overlap.
+    overlapping: BTreeSet<(BodyHandle, BodyHandle)>,
+    events: Vec<SensorEvent>,
+}
+
+impl SensorTracker {
+    pub fn events(&self) -> &[SensorEvent] {
+        &self.events
+    }
+
+    /// Replaces the overlapping pairs with `overlaps`, each a sensor and a body, and records the
+    /// events of the tick.
+    pub fn update(&mut self, overlaps: impl IntoIterator<Item = (BodyHandle, BodyHandle)>) {
+        let overlapping: BTreeSet<_> = overlaps.into_iter().collect();
+
+        let event = |kind| {
+            move |&(sensor, other): &(BodyHandle, BodyHandle)| SensorEvent {
+                kind,
+                sensor,
+                other,
+            }
+        };
+
+        self.events = overlapping
+            .difference(&self.overlapping)
+            .map(event(SensorEventKind::Enter))
+            .chain(
+                self.overlapping
+                    .difference(&overlapping)
+                    .map(event(SensorEventKind::Exit)),
+            )
+            .collect();
+
+        self.overlapping = overlapping;
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+use std::{fmt, sync::Arc};
+
+use super::BodyHandle;
+
+/// Decides which bodies collide with each other.
+///
+/// Two bodies collide if each one's category is in the other's mask, unless they share a
+/// non-zero group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CollisionFilter {
+    /// The layers the body is on, one per bit.
+    pub category: u32,
+    /// The layers the body collides with.
+    pub mask: u32,
+    /// Bodies in the same positive group always collide, and bodies in the same negative group
+    /// never do, whatever their categories and masks. 0 means no group.
+    pub group: i32,
+}
+
+impl Default for CollisionFilter {
+    fn default() -> Self {
+        Self {
+            category: 1,
+            mask: u32::MAX,
+            group: 0,
+        }
+    }
+}
+
+impl CollisionFilter {
+    pub fn should_collide(&self, other: &Self) -> bool {
+        if self.group != 0 && self.group == other.group {
+            return self.group > 0;
+        }
+
+        self.mask & other.category != 0 && other.mask & self.category != 0
+    }
+}
+
+/// A user predicate deciding whether two bodies may collide, on top of their filters.
+#[derive(Clone)]
+pub(super) struct PairFilter(pub Arc<dyn Fn(BodyHandle, BodyHandle) -> bool + Send + Sync>);
+
+impl fmt::Debug for PairFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PairFilter")
+    }
+}
+
+/// Everything deciding whether a pair of bodies collides.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Filters {
+    /// The collision filter of each body, by handle index.
+    pub static_filters: Vec<CollisionFilter>,
+    pub dynamic_filters: Vec<CollisionFilter>,
+    pub pair_filter: Option<PairFilter>,
+    /// The pairs of bodies connected by a joint that stops them colliding, in order.
+    pub connected: HashSet<(BodyHandle, BodyHandle)>,
+}
+
+impl Filters {
+    pub fn should_collide(&self, this: BodyHandle, that: BodyHandle) -> bool {
+        let filter = |handle| match handle {
+            BodyHandle::Static(handle) => &self.static_filters[handle.index()],
+            BodyHandle::Dynamic(handle) => &self.dynamic_filters[handle.index()],
+        };
+
+        filter(this).should_collide(filter(that))
+            && !self.connected.contains(&(this.min(that), this.max(that)))
+            && self
+                .pair_filter
+                .as_ref()
+                .is_none_or(|PairFilter(pair_filter)| pair_filter(this, that))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEBRIS: u32 = 1 << 1;
+
+    #[test]
+    fn test_categories_and_masks_must_match_both_ways() {
+        let default = CollisionFilter::default();
+        let debris = CollisionFilter {
+            category: DEBRIS,
+            mask: !DEBRIS,
+            group: 0,
+        };
+
+        assert!(default.should_collide(&debris));
+        assert!(!debris.should_collide(&debris));
+
+        let ghost = CollisionFilter { mask: 0, ..default };
+
+        assert!(!default.should_collide(&ghost));
+        assert!(!ghost.should_collide(&default));
+    }
+
+    #[test]
+    fn test_groups_override_categories_and_masks() {
+        let limb = CollisionFilter {
+            group: -1,
+            ..CollisionFilter::default()
+        };
+        let magnet = CollisionFilter {
+            mask: 0,
+            group: 2,
+            ..CollisionFilter::default()
+        };
+
+        assert!(!limb.should_collide(&limb));
+        assert!(limb.should_collide(&CollisionFilter::default()));
+        assert!(magnet.should_collide(&magnet));
+        assert!(!magnet.should_collide(&CollisionFilter::default()));
+    }
+}
@@ -1,70 +1,243 @@
-use macroquad::rand::gen_range;
+use std::f64::consts::TAU;
 
-use crate::{body::*, vec2::*};
+use rand_core::{RngCore, SeedableRng};
+use rand_pcg::Pcg64;
+
+use crate::{body::*, bounding_volume::BoundingVolume, vec2::*};
 
 use super::World;
 
-fn random_position(width: f64, height: f64, offset: f64) -> Vec2D {
-    Vec2D {
-        x: gen_range(offset, width - offset),
-        y: gen_range(offset, height - offset),
+/// How a random quantity of the generated bodies is distributed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distribution {
+    Constant(f64),
+    /// Uniform in `[min, max)`.
+    Uniform {
+        min: f64,
+        max: f64,
+    },
+    Normal {
+        mean: f64,
+        standard_deviation: f64,
+    },
+}
+
+impl Distribution {
+    fn sample(&self, rng: &mut impl RngCore) -> f64 {
+        match *self {
+            Distribution::Constant(value) => value,
+            Distribution::Uniform { min, max } => gen_range(rng, min, max),
+            Distribution::Normal {
+                mean,
+                standard_deviation,
+            } => {
+                // Box-Muller transform. `1 - u` is in (0, 1], so its logarithm is finite.
+                let u = 1. - gen_range(rng, 0., 1.);
+                let v = gen_range(rng, 0., 1.);
+
+                mean + standard_deviation * (-2. * u.ln()).sqrt() * (TAU * v).cos()
+            }
+        }
     }
 }
 
-const MAX_INITIAL_VELOCITY: f64 = 5.;
+/// The relative proportions of each shape among the generated bodies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapeMix {
+    pub circles: f64,
+    pub rectangles: f64,
+    pub polygons: f64,
+    pub capsules: f64,
+}
 
-fn random_velocity() -> Vec2D {
-    Vec2D {
-        x: gen_range(-MAX_INITIAL_VELOCITY, MAX_INITIAL_VELOCITY),
-        y: gen_range(-MAX_INITIAL_VELOCITY, MAX_INITIAL_VELOCITY),
+impl Default for ShapeMix {
+    fn default() -> Self {
+        Self {
+            circles: 1.,
+            rectangles: 1.,
+            polygons: 0.,
+            capsules: 0.,
+        }
     }
 }
 
-fn random_base_dynamic_body(width: f64, height: f64, offset: f64) -> BaseDynamicBody {
-    let position = random_position(width, height, offset);
+impl ShapeMix {
+    /// Whether shapes can be drawn from the mix: no weight is negative or NaN, and they add up to
+    /// a positive, finite number.
+    fn is_valid(&self) -> bool {
+        let weights = [self.circles, self.rectangles, self.polygons, self.capsules];
 
-    let velocity = random_velocity();
+        let total: f64 = weights.iter().sum();
 
-    let coefficient_of_restitution = gen_range(0., 1.);
+        weights.iter().all(|&weight| weight >= 0.) && total.is_finite() && total > 0.
+    }
+}
 
-    let mass = gen_range(0., 1.) + 0.000001;
+/// Describes a randomly generated world: `num_bodies` dynamic bodies inside a box of borders.
+///
+/// Generating the same spec twice gives bit-identical worlds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationSpec {
+    pub seed: u64,
+    pub width: f64,
+    pub height: f64,
+    /// How far the borders are from the edges of the `width` by `height` area.
+    pub offset: f64,
+    pub num_bodies: u32,
+    pub gravity: Vec2D,
+    pub shape_mix: ShapeMix,
+    pub mass: Distribution,
+    /// The radius of circles and of the circle around polygons, the sum of the half extents of
+    /// rectangles, and the sum of the half length and the radius of capsules.
+    pub size: Distribution,
+    pub restitution: Distribution,
+    /// Each component of the initial velocity is drawn from this.
+    pub velocity: Distribution,
+    /// Whether to keep the initial bounding volumes of the bodies from overlapping. Bodies that
+    /// cannot be placed after [`MAX_PLACEMENT_ATTEMPTS`] are left out.
+    pub avoid_overlap: bool,
+}
 
-    BaseDynamicBody {
-        position,
-        velocity,
-        coefficient_of_restitution,
-        inverse_mass: 1. / mass,
+impl Default for GenerationSpec {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            width: 1920.,
+            height: 1080.,
+            offset: 10.,
+            num_bodies: 1000,
+            gravity: Vec2D { x: 0., y: 100. },
+            shape_mix: ShapeMix::default(),
+            mass: Distribution::Uniform { min: 0.1, max: 1. },
+            size: Distribution::Uniform { min: 2., max: 10. },
+            restitution: Distribution::Uniform { min: 0., max: 1. },
+            velocity: Distribution::Uniform { min: -5., max: 5. },
+            avoid_overlap: false,
+        }
     }
 }
 
-const SIZE_TO_MASS_RATIO: f64 = 10.;
+pub const MAX_PLACEMENT_ATTEMPTS: u32 = 100;
+
+const MIN_MASS: f64 = 0.000001;
+const MIN_SIZE: f64 = 0.1;
 
-fn random_circle(width: f64, height: f64, offset: f64) -> Circle {
-    let body = random_base_dynamic_body(width, height, offset);
+/// A uniformly distributed number in `[low, high)`.
+fn gen_range(rng: &mut impl RngCore, low: f64, high: f64) -> f64 {
+    // The top 53 bits fill the mantissa of a double in [0, 1).
+    let unit = (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
 
-    Circle {
-        body,
-        radius: SIZE_TO_MASS_RATIO / body.inverse_mass,
+    low + (high - low) * unit
+}
+
+fn random_position(rng: &mut impl RngCore, spec: &GenerationSpec) -> Vec2D {
+    Vec2D {
+        x: gen_range(rng, spec.offset, spec.width - spec.offset),
+        y: gen_range(rng, spec.offset, spec.height - spec.offset),
     }
 }
 
-fn random_rectangle(width: f64, height: f64, offset: f64) -> Rectangle {
-    let body = random_base_dynamic_body(width, height, offset);
+fn random_base_dynamic_body(rng: &mut impl RngCore, spec: &GenerationSpec) -> BaseDynamicBody {
+    let position = random_position(rng, spec);
+
+    let velocity = Vec2D {
+        x: spec.velocity.sample(rng),
+        y: spec.velocity.sample(rng),
+    };
 
-    let aspect_ratio = gen_range(0.25, 0.75);
+    let coefficient_of_restitution = spec.restitution.sample(rng).clamp(0., 1.);
 
-    let half_width = aspect_ratio * SIZE_TO_MASS_RATIO / body.inverse_mass;
-    let half_height = (1. - aspect_ratio) * SIZE_TO_MASS_RATIO / body.inverse_mass;
+    let mass = spec.mass.sample(rng).max(MIN_MASS);
+
+    BaseDynamicBody::new(position, velocity, coefficient_of_restitution, 1. / mass)
+}
 
-    Rectangle {
-        body,
-        half_width,
-        half_height,
+fn random_rectangle(rng: &mut impl RngCore, body: BaseDynamicBody, size: f64) -> Rectangle {
+    let aspect_ratio = gen_range(rng, 0.25, 0.75);
+
+    Rectangle::new(body, aspect_ratio * size, (1. - aspect_ratio) * size)
+}
+
+fn random_polygon(rng: &mut impl RngCore, body: BaseDynamicBody, size: f64) -> Polygon {
+    let vertex_count = 3 + (rng.next_u32() as usize) % (MAX_POLYGON_VERTICES - 2);
+
+    // Points on a circle, in order, always form a convex polygon.
+    let mut vertices = [ZERO; MAX_POLYGON_VERTICES];
+
+    for (i, vertex) in vertices[..vertex_count].iter_mut().enumerate() {
+        let angle = (i as f64 + gen_range(rng, 0., 0.5)) * TAU / vertex_count as f64;
+
+        *vertex = Vec2D {
+            x: size * angle.cos(),
+            y: size * angle.sin(),
+        };
+    }
+
+    Polygon::new(body, &vertices[..vertex_count])
+        .expect("vertices on a circle form a convex polygon")
+}
+
+fn random_capsule(rng: &mut impl RngCore, body: BaseDynamicBody, size: f64) -> Capsule {
+    let aspect_ratio = gen_range(rng, 0.25, 0.75);
+
+    Capsule::new(body, aspect_ratio * size, (1. - aspect_ratio) * size)
+        .expect("the size is positive")
+}
+
+fn random_dynamic_body(rng: &mut impl RngCore, spec: &GenerationSpec) -> DynamicBody {
+    let body = random_base_dynamic_body(rng, spec);
+    let size = spec.size.sample(rng).max(MIN_SIZE);
+
+    let ShapeMix {
+        circles,
+        rectangles,
+        polygons,
+        capsules,
+    } = spec.shape_mix;
+
+    let choice = gen_range(rng, 0., circles + rectangles + polygons + capsules);
+
+    if choice < circles {
+        DynamicBody::Circle(Circle::new(body, size))
+    } else if choice < circles + rectangles {
+        DynamicBody::Rectangle(random_rectangle(rng, body, size))
+    } else if choice < circles + rectangles + polygons {
+        DynamicBody::Polygon(random_polygon(rng, body, size))
+    } else {
+        DynamicBody::Capsule(random_capsule(rng, body, size))
     }
 }
 
 impl World {
-    pub fn generate(width: f64, height: f64, offset: f64, num_bodies: u32, gravity: Vec2D) -> Self {
+    /// Generates a world from `spec`, drawing from a [`Pcg64`] seeded with `spec.seed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `spec.shape_mix` is invalid, as [`World::generate_with_rng`] does.
+    pub fn generate(spec: &GenerationSpec) -> Self {
+        Self::generate_with_rng(spec, &mut Pcg64::seed_from_u64(spec.seed))
+    }
+
+    /// Generates a world from `spec`, drawing from `rng` instead, so `spec.seed` is ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a weight of `spec.shape_mix` is negative or NaN, or if they do not add up to a
+    /// positive, finite number.
+    pub fn generate_with_rng(spec: &GenerationSpec, rng: &mut impl RngCore) -> Self {
+        assert!(
+            spec.shape_mix.is_valid(),
+            "invalid shape mix: {:?}",
+            spec.shape_mix
+        );
+
+        let GenerationSpec {
+            width,
+            height,
+            offset,
+            ..
+        } = *spec;
+
         let top_border = Line {
             normal: UNIT_DOWN,
             origin_distance: -offset,
@@ -82,27 +255,177 @@ impl World {
             origin_distance: -offset,
         };
 
-        let static_bodies: Vec<_> = [top_border, right_border, bottom_border, left_border]
-            .into_iter()
-            .map(StaticBody::Line)
+        let mut world = World::new(spec.gravity);
+
+        for border in [top_border, right_border, bottom_border, left_border] {
+            world.add_static(StaticBody::Line(border));
+        }
+
+        let mut placed: Vec<BoundingVolume> = vec![];
+
+        for _ in 0..spec.num_bodies {
+            let mut body = random_dynamic_body(rng, spec);
+
+            if spec.avoid_overlap {
+                let mut attempts = 1;
+
+                let is_overlapping = |body: &DynamicBody| {
+                    let bounding_volume = body.to_bounding_volume();
+
+                    placed
+                        .iter()
+                        .any(|other| other.is_intersecting(&bounding_volume))
+                };
+
+                while is_overlapping(&body) && attempts < MAX_PLACEMENT_ATTEMPTS {
+                    body.as_mut().position = random_position(rng, spec);
+                    attempts += 1;
+                }
+
+                if is_overlapping(&body) {
+                    continue;
+                }
+
+                placed.push(body.to_bounding_volume());
+            }
+
+            world.add_dynamic(body);
+        }
+
+        world
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate_positions(spec: &GenerationSpec) -> Vec<Vec2D> {
+        World::generate(spec)
+            .dynamic_bodies()
+            .map(|(_, body)| body.as_ref().position)
+            .collect()
+    }
+
+    fn create_spec(seed: u64) -> GenerationSpec {
+        GenerationSpec {
+            seed,
+            width: 800.,
+            height: 600.,
+            num_bodies: 50,
+            shape_mix: ShapeMix {
+                circles: 1.,
+                rectangles: 1.,
+                polygons: 1.,
+                capsules: 1.,
+            },
+            ..GenerationSpec::default()
+        }
+    }
+
+    #[test]
+    fn test_shape_mixes_that_cannot_be_drawn_from_are_rejected() {
+        for shape_mix in [
+            ShapeMix {
+                circles: 0.,
+                rectangles: 0.,
+                polygons: 0.,
+                capsules: 0.,
+            },
+            ShapeMix {
+                circles: 2.,
+                rectangles: -1.,
+                ..ShapeMix::default()
+            },
+            ShapeMix {
+                circles: f64::NAN,
+                ..ShapeMix::default()
+            },
+            ShapeMix {
+                circles: f64::INFINITY,
+                ..ShapeMix::default()
+            },
+        ] {
+            let spec = GenerationSpec {
+                shape_mix,
+                ..create_spec(0)
+            };
+
+            assert!(std::panic::catch_unwind(|| World::generate(&spec)).is_err());
+        }
+
+        assert!(ShapeMix::default().is_valid());
+    }
+
+    #[test]
+    fn test_generate_is_deterministic_for_a_given_seed() {
+        let positions = generate_positions(&create_spec(42));
+
+        assert_eq!(positions, generate_positions(&create_spec(42)));
+        assert_ne!(positions, generate_positions(&create_spec(43)));
+    }
+
+    #[test]
+    fn test_generate_with_rng_matches_generate_with_the_same_rng() {
+        let spec = create_spec(42);
+        let world = World::generate_with_rng(&spec, &mut Pcg64::seed_from_u64(42));
+
+        let positions: Vec<_> = world
+            .dynamic_bodies()
+            .map(|(_, body)| body.as_ref().position)
             .collect();
 
-        let mut dynamic_bodies = vec![];
+        assert_eq!(positions, generate_positions(&spec));
+    }
 
-        let circles =
-            (0..num_bodies).map(|_| DynamicBody::Circle(random_circle(width, height, offset)));
+    #[test]
+    fn test_generate_keeps_bodies_inside_the_borders() {
+        let spec = create_spec(7);
 
-        dynamic_bodies.extend(circles);
+        for position in generate_positions(&spec) {
+            assert!((10. ..790.).contains(&position.x));
+            assert!((10. ..590.).contains(&position.y));
+        }
+    }
 
-        let rectangles = (0..num_bodies)
-            .map(|_| DynamicBody::Rectangle(random_rectangle(width, height, offset)));
+    #[test]
+    fn test_generate_follows_the_shape_mix() {
+        let spec = GenerationSpec {
+            shape_mix: ShapeMix {
+                circles: 0.,
+                rectangles: 0.,
+                polygons: 1.,
+                capsules: 0.,
+            },
+            ..create_spec(1)
+        };
 
-        dynamic_bodies.extend(rectangles);
+        let world = World::generate(&spec);
 
-        Self {
-            static_bodies,
-            dynamic_bodies,
-            gravity,
+        assert_eq!(world.dynamic_body_count(), 50);
+        assert!(
+            world
+                .dynamic_bodies()
+                .all(|(_, body)| matches!(body, DynamicBody::Polygon(_)))
+        );
+    }
+
+    #[test]
+    fn test_generate_can_avoid_overlap() {
+        let spec = GenerationSpec {
+            avoid_overlap: true,
+            ..create_spec(3)
+        };
+
+        let bounding_volumes: Vec<_> = World::generate(&spec)
+            .dynamic_bodies()
+            .map(|(_, body)| body.to_bounding_volume())
+            .collect();
+
+        for (i, this) in bounding_volumes.iter().enumerate() {
+            for that in &bounding_volumes[i + 1..] {
+                assert!(!this.is_intersecting(that));
+            }
         }
     }
 }
@@ -0,0 +1,28 @@
+use crate::arena::Handle;
+use crate::body::{DynamicBody, StaticBody};
+use crate::simulation::forces::ForceGenerator;
+use crate::simulation::joints::Joint;
+
+pub type DynamicBodyHandle = Handle<DynamicBody>;
+pub type StaticBodyHandle = Handle<StaticBody>;
+pub type JointHandle = Handle<Joint>;
+pub type ForceGeneratorHandle = Handle<ForceGenerator>;
+
+/// A handle to either kind of body in a [`World`](super::World).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BodyHandle {
+    Static(StaticBodyHandle),
+    Dynamic(DynamicBodyHandle),
+}
+
+impl From<StaticBodyHandle> for BodyHandle {
+    fn from(handle: StaticBodyHandle) -> Self {
+        Self::Static(handle)
+    }
+}
+
+impl From<DynamicBodyHandle> for BodyHandle {
+    fn from(handle: DynamicBodyHandle) -> Self {
+        Self::Dynamic(handle)
+    }
+}
@@ -1,288 +1,1784 @@
+mod broadphase;
+mod chain;
+mod combine_rule;
+mod config;
+mod continuous;
+mod events;
+mod filter;
 mod generation;
+mod handles;
+mod queries;
+mod solver;
+
+pub use broadphase::*;
+pub use chain::*;
+pub use combine_rule::*;
+pub use config::*;
+pub use events::*;
+pub use filter::*;
+pub use generation::*;
+pub use handles::*;
+pub use queries::*;
+
+use continuous::*;
+use solver::*;
+
+use std::collections::BTreeSet;
+use std::sync::{Arc, OnceLock};
 
 use super::collisions::*;
+use super::forces::ForceGenerator;
+use super::joints::Joint;
+use crate::arena::{Arena, Handle};
 use crate::body::*;
 use crate::bounding_volume::BoundingVolume;
 use crate::vec2::*;
 
 #[derive(Debug, Clone)]
 pub struct World {
-    pub static_bodies: Vec<StaticBody>,
-    pub dynamic_bodies: Vec<DynamicBody>,
+    static_bodies: Arena<StaticBody>,
+    dynamic_bodies: Arena<DynamicBody>,
+    joints: Arena<Joint>,
+    force_generators: Arena<ForceGenerator>,
     pub gravity: Vec2D,
+    pub config: WorldConfig,
+    contact_cache: ContactCache,
+    contact_tracker: ContactTracker,
+    sensor_tracker: SensorTracker,
+    broadphase: Box<dyn Broadphase>,
+    /// The dynamic bodies handed out mutably since the broadphase was last updated, which queries
+    /// check directly since they may have moved.
+    stale_bodies: BTreeSet<DynamicBodyHandle>,
+    /// Whether all the dynamic bodies may have moved.
+    all_bodies_stale: bool,
+    filters: Filters,
+    /// Whether each static body is a sensor region, by handle index.
+    static_sensors: Vec<bool>,
+    /// Built lazily, and cleared whenever the static bodies may have changed.
+    static_tree: OnceLock<StaticTree>,
 }
 
-impl World {
-    fn apply_gravity(&mut self, elapsed: f64) {
-        let gravity = &self.gravity * elapsed;
+/// The static bodies, arranged to be queried by the bounding volume of each dynamic body.
+#[derive(Debug, Clone)]
+struct StaticTree {
+    /// The parts of the static bodies that have a bounding volume.
+    tree: Option<BoundingVolumeHierarchyTree<(StaticBodyHandle, usize)>>,
+    /// The infinite bodies, which are tested against every dynamic body.
+    infinite_bodies: Vec<StaticBodyHandle>,
+}
 
-        for body in &mut self.dynamic_bodies {
-            body.as_mut().velocity += &gravity;
+impl StaticTree {
+    fn new(bodies: &Arena<StaticBody>) -> Self {
+        let mut bounding_volumes = vec![];
+        let mut infinite_bodies = vec![];
+
+        for (handle, body) in bodies.iter() {
+            for part in 0..body.part_count() {
+                match body.part_bounding_volume(part) {
+                    Some(bounding_volume) => {
+                        bounding_volumes.push(((handle, part), bounding_volume))
+                    }
+                    None => infinite_bodies.push(handle),
+                }
+            }
+        }
+
+        Self {
+            tree: BoundingVolumeHierarchyTree::new(&mut bounding_volumes),
+            infinite_bodies,
         }
     }
 
-    fn detect_dynamic_collisions(&self) -> Vec<(Contact, usize, usize)> {
-        let mut bounding_volumes: Vec<_> = self
-            .dynamic_bodies
+    /// Calls `callback` with every infinite body, and every part whose bounding volume intersects
+    /// `bounding_volume`.
+    fn query(
+        &self,
+        bounding_volume: &BoundingVolume,
+        mut callback: impl FnMut(StaticBodyHandle, usize),
+    ) {
+        for &handle in &self.infinite_bodies {
+            callback(handle, 0);
+        }
+
+        if let Some(tree) = &self.tree {
+            tree.for_each_overlapping_body(bounding_volume, &mut |(handle, part)| {
+                callback(handle, part)
+            });
+        }
+    }
+}
+
+/// The kinds of bodies stored in a [`World`], so that its accessors can take either kind of handle.
+pub trait WorldBody: Sized {
+    fn arena(world: &World) -> &Arena<Self>;
+    fn arena_mut(world: &mut World) -> &mut Arena<Self>;
+    fn get_mut(world: &mut World, handle: Handle<Self>) -> Option<&mut Self>;
+    fn filters_mut(world: &mut World) -> &mut Vec<CollisionFilter>;
+    fn remove(world: &mut World, handle: Handle<Self>) -> Option<Self>;
+}
+
+impl WorldBody for StaticBody {
+    fn arena(world: &World) -> &Arena<Self> {
+        &world.static_bodies
+    }
+
+    fn arena_mut(world: &mut World) -> &mut Arena<Self> {
+        world.static_tree.take();
+
+        &mut world.static_bodies
+    }
+
+    fn get_mut(world: &mut World, handle: Handle<Self>) -> Option<&mut Self> {
+        Self::arena_mut(world).get_mut(handle)
+    }
+
+    fn filters_mut(world: &mut World) -> &mut Vec<CollisionFilter> {
+        &mut world.filters.static_filters
+    }
+
+    fn remove(world: &mut World, handle: Handle<Self>) -> Option<Self> {
+        Self::arena_mut(world).remove(handle)
+    }
+}
+
+impl WorldBody for DynamicBody {
+    fn arena(world: &World) -> &Arena<Self> {
+        &world.dynamic_bodies
+    }
+
+    fn arena_mut(world: &mut World) -> &mut Arena<Self> {
+        &mut world.dynamic_bodies
+    }
+
+    fn get_mut(world: &mut World, handle: Handle<Self>) -> Option<&mut Self> {
+        let body = world.dynamic_bodies.get_mut(handle)?;
+
+        world.stale_bodies.insert(handle);
+
+        Some(body)
+    }
+
+    fn filters_mut(world: &mut World) -> &mut Vec<CollisionFilter> {
+        &mut world.filters.dynamic_filters
+    }
+
+    fn remove(world: &mut World, handle: Handle<Self>) -> Option<Self> {
+        let body = world.dynamic_bodies.remove(handle)?;
+
+        world.broadphase.remove(handle);
+
+        let attached_joints: Vec<_> = world
+            .joints
             .iter()
-            .enumerate()
-            .map(|(index, body)| (index, body.to_bounding_volume()))
+            .filter(|(_, joint)| joint.bodies().is_attached_to(handle))
+            .map(|(joint_handle, _)| joint_handle)
             .collect();
 
-        let Some(bvh) = BoundingVolumeHierarchyTree::new(&mut bounding_volumes) else {
-            return vec![];
-        };
+        for joint_handle in attached_joints {
+            world.joints.remove(joint_handle);
+        }
 
-        let mut contacts: Vec<(Contact, usize, usize)> = vec![];
+        let attached_force_generators: Vec<_> = world
+            .force_generators
+            .iter()
+            .filter(|(_, generator)| generator.is_attached_to(handle))
+            .map(|(generator_handle, _)| generator_handle)
+            .collect();
 
-        for (i, bounding_volume) in &bounding_volumes {
-            let i = *i;
-            let this = &self.dynamic_bodies[i];
+        for generator_handle in attached_force_generators {
+            world.force_generators.remove(generator_handle);
+        }
 
-            for j in bvh.get_overlapping_bodies(bounding_volume) {
-                if j <= i {
-                    continue;
-                }
+        Some(body)
+    }
+}
 
-                let that = &self.dynamic_bodies[j];
+impl World {
+    pub fn new(gravity: Vec2D) -> Self {
+        Self::with_config(gravity, WorldConfig::default())
+    }
 
-                let Some(contact) = generate_contact_dynamic(this, that) else {
-                    continue;
-                };
+    pub fn with_config(gravity: Vec2D, config: WorldConfig) -> Self {
+        Self {
+            static_bodies: Arena::new(),
+            dynamic_bodies: Arena::new(),
+            joints: Arena::new(),
+            force_generators: Arena::new(),
+            gravity,
+            config,
+            contact_cache: ContactCache::default(),
+            contact_tracker: ContactTracker::default(),
+            sensor_tracker: SensorTracker::default(),
+            broadphase: Box::new(TreeBroadphase::new()),
+            stale_bodies: BTreeSet::new(),
+            all_bodies_stale: false,
+            filters: Filters::default(),
+            static_sensors: vec![],
+            static_tree: OnceLock::new(),
+        }
+    }
 
-                if contact.distance >= 0. {
-                    continue;
-                }
+    pub fn add_static(&mut self, body: StaticBody) -> StaticBodyHandle {
+        let handle = StaticBody::arena_mut(self).insert(body);
 
-                contacts.push((contact, i, j));
-            }
+        self.reset_collision_filter(handle);
+
+        if self.static_sensors.len() <= handle.index() {
+            self.static_sensors.resize(handle.index() + 1, false);
         }
 
-        contacts
+        self.static_sensors[handle.index()] = false;
+
+        handle
     }
 
-    fn handle_collisions(&mut self) {
-        for this in &self.static_bodies {
-            for that in &mut self.dynamic_bodies {
-                handle_collision_static(this, that);
-            }
-        }
+    /// Adds a static sensor region, which reports the bodies entering and leaving it as
+    /// [`SensorEvent`]s without blocking them, e.g. a goal area or a kill zone.
+    pub fn add_static_sensor(&mut self, body: StaticBody) -> StaticBodyHandle {
+        let handle = self.add_static(body);
 
-        let contacts = self.detect_dynamic_collisions();
+        self.static_sensors[handle.index()] = true;
 
-        for _ in 0..10 {
-            for (contact, i, j) in &contacts {
-                if let Some(impulse) = get_impulse(
-                    contact,
-                    self.dynamic_bodies[*i].as_ref(),
-                    self.dynamic_bodies[*j].as_ref(),
-                ) {
-                    let this_body = self.dynamic_bodies[*i].as_mut();
-                    this_body.velocity += &(&impulse * this_body.inverse_mass);
-
-                    let that_body = self.dynamic_bodies[*j].as_mut();
-                    that_body.velocity -= &(&impulse * that_body.inverse_mass);
-                }
+        handle
+    }
+
+    /// Whether a body is a static sensor region, or a dynamic body with
+    /// [`BaseDynamicBody::is_sensor`] set. Invalid handles are not sensors.
+    pub fn is_sensor(&self, handle: BodyHandle) -> bool {
+        match handle {
+            BodyHandle::Static(handle) => {
+                self.contains(handle) && self.static_sensors[handle.index()]
+            }
+            BodyHandle::Dynamic(handle) => {
+                self.get(handle).is_some_and(|body| body.as_ref().is_sensor)
             }
         }
+    }
+
+    pub fn add_dynamic(&mut self, body: DynamicBody) -> DynamicBodyHandle {
+        let bounding_volume = body.to_bounding_volume();
+        let handle = self.dynamic_bodies.insert(body);
 
-        for (contact, i, j) in &contacts {
-            let correction = get_correction(
-                contact,
-                self.dynamic_bodies[*i].as_ref(),
-                self.dynamic_bodies[*j].as_ref(),
-            );
+        self.broadphase.insert(handle, &bounding_volume);
+        self.reset_collision_filter(handle);
 
-            let this_body = self.dynamic_bodies[*i].as_mut();
-            this_body.position += &(&correction * this_body.inverse_mass);
+        handle
+    }
+
+    fn reset_collision_filter<T: WorldBody>(&mut self, handle: Handle<T>) {
+        let filters = T::filters_mut(self);
 
-            let that_body = self.dynamic_bodies[*j].as_mut();
-            that_body.position -= &(&correction * that_body.inverse_mass);
+        if filters.len() <= handle.index() {
+            filters.resize(handle.index() + 1, CollisionFilter::default());
         }
+
+        filters[handle.index()] = CollisionFilter::default();
     }
 
-    fn integrate_bodies(&mut self, elapsed: f64) {
-        for body in self.dynamic_bodies.iter_mut() {
-            body.as_mut().integrate(elapsed);
+    /// The collision filter of a body, which is [`CollisionFilter::default`] when it is added.
+    pub fn collision_filter_mut<T: WorldBody>(
+        &mut self,
+        handle: Handle<T>,
+    ) -> Option<&mut CollisionFilter> {
+        if !self.contains(handle) {
+            return None;
         }
+
+        Some(&mut T::filters_mut(self)[handle.index()])
     }
 
-    pub fn tick(&mut self, elapsed: f64) {
-        self.apply_gravity(elapsed);
-        self.handle_collisions();
-        self.integrate_bodies(elapsed);
+    /// Sets a predicate that must also return `true` for a pair of bodies to collide, after their
+    /// collision filters.
+    pub fn set_pair_filter(
+        &mut self,
+        filter: impl Fn(BodyHandle, BodyHandle) -> bool + Send + Sync + 'static,
+    ) {
+        self.filters.pair_filter = Some(PairFilter(Arc::new(filter)));
+    }
+
+    pub fn clear_pair_filter(&mut self) {
+        self.filters.pair_filter = None;
     }
-}
 
-fn handle_collision_static(this: &StaticBody, that: &mut DynamicBody) {
-    let contact = generate_contact_static(this, that);
+    /// The contacts that began, persisted or ended during the latest tick: those that began or
+    /// persisted first, in the order they were solved, then those that ended.
+    ///
+    /// Removing a body ends its contacts on the next tick, with its stale handle.
+    pub fn collision_events(&self) -> &[CollisionEvent] {
+        self.contact_tracker.events()
+    }
 
-    if contact.distance >= 0. {
-        return;
+    /// The bodies that entered or left a sensor during the latest tick.
+    ///
+    /// Removing a body or a sensor ends its overlaps on the next tick, with its stale handle.
+    pub fn sensor_events(&self) -> &[SensorEvent] {
+        self.sensor_tracker.events()
     }
 
-    let this_body = BaseDynamicBody {
-        position: ZERO,
-        velocity: ZERO,
-        coefficient_of_restitution: 1.,
-        inverse_mass: 0.,
-    };
-    let that_body = that.as_mut();
+    /// Replaces the broadphase used to find the pairs of dynamic bodies that may collide. By
+    /// default, this is a [`TreeBroadphase`].
+    pub fn set_broadphase(&mut self, mut broadphase: impl Broadphase + 'static) {
+        for (handle, body) in self.dynamic_bodies.iter() {
+            broadphase.insert(handle, &body.to_bounding_volume());
+        }
 
-    if let Some(impulse) = get_impulse(&contact, &this_body, that_body) {
-        that_body.velocity -= &(&impulse * that_body.inverse_mass);
+        self.broadphase = Box::new(broadphase);
     }
 
-    let correction = get_correction(&contact, &this_body, that_body);
-    that_body.position -= &(&correction * that_body.inverse_mass);
-}
+    /// Adds a joint between bodies of the world, or returns `None` if they are not all in it.
+    pub fn add_joint(&mut self, joint: impl Into<Joint>) -> Option<JointHandle> {
+        let joint = joint.into();
+
+        joint
+            .bodies()
+            .are_valid(&self.dynamic_bodies)
+            .then(|| self.joints.insert(joint))
+    }
 
-fn get_impulse(
-    contact: &Contact,
-    this_body: &BaseDynamicBody,
-    that_body: &BaseDynamicBody,
-) -> Option<Vec2D> {
-    let relative_velocity = &that_body.velocity - &this_body.velocity;
-    let relative_velocity_dot_normal = relative_velocity.dot_product(&contact.normal);
+    pub fn remove_joint(&mut self, handle: JointHandle) -> Option<Joint> {
+        self.joints.remove(handle)
+    }
 
-    if relative_velocity_dot_normal > 0. {
-        return None;
+    pub fn joint(&self, handle: JointHandle) -> Option<&Joint> {
+        self.joints.get(handle)
     }
 
-    let coefficient_of_restitution = f64::min(
-        this_body.coefficient_of_restitution,
-        that_body.coefficient_of_restitution,
-    );
+    /// A joint whose bodies are changed to ones that are not in the world, or to the same body
+    /// twice, is left out of the simulation until they are valid again.
+    pub fn joint_mut(&mut self, handle: JointHandle) -> Option<&mut Joint> {
+        self.joints.get_mut(handle)
+    }
 
-    let impulse_amount = (1. + coefficient_of_restitution) * relative_velocity_dot_normal
-        / (this_body.inverse_mass + that_body.inverse_mass);
+    pub fn joints(&self) -> impl Iterator<Item = (JointHandle, &Joint)> {
+        self.joints.iter()
+    }
 
-    Some(&contact.normal * impulse_amount)
-}
+    /// Adds a generator of forces on bodies of the world, or returns `None` if they are not all
+    /// in it.
+    pub fn add_force_generator(
+        &mut self,
+        generator: impl Into<ForceGenerator>,
+    ) -> Option<ForceGeneratorHandle> {
+        let generator = generator.into();
 
-const CORRECTION_THRESHOLD: f64 = 0.05;
-const CORRECTION_PERCENTAGE: f64 = 0.4;
+        generator
+            .is_valid(&self.dynamic_bodies)
+            .then(|| self.force_generators.insert(generator))
+    }
 
-fn get_correction(
-    contact: &Contact,
-    this_body: &BaseDynamicBody,
-    that_body: &BaseDynamicBody,
-) -> Vec2D {
-    let correction_amount = (contact.distance + CORRECTION_THRESHOLD).min(0.)
-        * CORRECTION_PERCENTAGE
-        / (this_body.inverse_mass + that_body.inverse_mass);
+    pub fn remove_force_generator(
+        &mut self,
+        handle: ForceGeneratorHandle,
+    ) -> Option<ForceGenerator> {
+        self.force_generators.remove(handle)
+    }
 
-    &contact.normal * correction_amount
-}
+    pub fn force_generator(&self, handle: ForceGeneratorHandle) -> Option<&ForceGenerator> {
+        self.force_generators.get(handle)
+    }
 
-#[derive(Debug, Clone, PartialEq)]
-enum BoundingVolumeHierarchyTree {
-    Leaf(BoundingVolume, usize),
-    Node(BoundingVolume, Box<Self>, Box<Self>),
-}
+    /// A generator whose bodies are changed to ones that are not in the world is left out of the
+    /// simulation until they are valid again.
+    pub fn force_generator_mut(
+        &mut self,
+        handle: ForceGeneratorHandle,
+    ) -> Option<&mut ForceGenerator> {
+        self.force_generators.get_mut(handle)
+    }
 
-impl BoundingVolumeHierarchyTree {
-    fn new(bounding_volumes: &mut [(usize, BoundingVolume)]) -> Option<Self> {
-        match bounding_volumes {
-            [] => None,
-            [(index, bounding_volume)] => {
-                Some(BoundingVolumeHierarchyTree::Leaf(*bounding_volume, *index))
+    pub fn force_generators(
+        &self,
+    ) -> impl Iterator<Item = (ForceGeneratorHandle, &ForceGenerator)> {
+        self.force_generators.iter()
+    }
+
+    /// Removes a body from the world, returning it if the handle was still valid. Removing a
+    /// dynamic body also removes its joints and force generators.
+    pub fn remove<T: WorldBody>(&mut self, handle: Handle<T>) -> Option<T> {
+        T::remove(self, handle)
+    }
+
+    pub fn contains<T: WorldBody>(&self, handle: Handle<T>) -> bool {
+        T::arena(self).contains(handle)
+    }
+
+    pub fn get<T: WorldBody>(&self, handle: Handle<T>) -> Option<&T> {
+        T::arena(self).get(handle)
+    }
+
+    pub fn get_mut<T: WorldBody>(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        T::get_mut(self, handle)
+    }
+
+    pub fn static_bodies(&self) -> impl Iterator<Item = (StaticBodyHandle, &StaticBody)> {
+        self.static_bodies.iter()
+    }
+
+    pub fn static_bodies_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (StaticBodyHandle, &mut StaticBody)> {
+        StaticBody::arena_mut(self).iter_mut()
+    }
+
+    pub fn dynamic_bodies(&self) -> impl Iterator<Item = (DynamicBodyHandle, &DynamicBody)> {
+        self.dynamic_bodies.iter()
+    }
+
+    pub fn dynamic_bodies_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (DynamicBodyHandle, &mut DynamicBody)> {
+        self.all_bodies_stale = true;
+
+        self.dynamic_bodies.iter_mut()
+    }
+
+    pub fn static_body_count(&self) -> usize {
+        self.static_bodies.len()
+    }
+
+    pub fn dynamic_body_count(&self) -> usize {
+        self.dynamic_bodies.len()
+    }
+
+    fn apply_forces(&mut self) {
+        for generator in self.force_generators.values() {
+            // They may have been changed to invalid bodies through `force_generator_mut`.
+            if generator.is_valid(&self.dynamic_bodies) {
+                generator.apply(&mut self.dynamic_bodies);
             }
-            [(_, first_volume), other_volumes @ ..] => {
-                let overall_bounding_volume = other_volumes
-                    .iter()
-                    .fold(*first_volume, |accumulator, (_, bounding_volume)| {
-                        accumulator.union(bounding_volume)
-                    });
+        }
+    }
 
-                let BoundingVolume {
-                    top_left: Vec2D { x: min_x, y: min_y },
-                    bottom_right: Vec2D { x: max_x, y: max_y },
-                } = overall_bounding_volume;
+    /// Changes the velocities by gravity and the accumulated forces.
+    fn apply_gravity(&mut self, elapsed: f64) {
+        let gravity = &self.gravity * elapsed;
 
-                let is_main_axis_x = (max_x - min_x) > (max_y - min_y);
+        for body in self.dynamic_bodies.values_mut() {
+            body.as_mut().velocity += &gravity;
+            body.as_mut().integrate_forces(elapsed);
+        }
+    }
 
-                let overall_mid_point = if is_main_axis_x {
-                    (min_x + max_x) / 2.
-                } else {
-                    (min_y + max_y) / 2.
-                };
+    /// Updates the broadphase for the bodies handed out mutably since it was last updated.
+    fn update_stale_bodies(&mut self, elapsed: f64) {
+        if self.all_bodies_stale {
+            self.update_broadphase(elapsed);
 
-                let mut split_index = 0;
+            return;
+        }
 
-                for i in 0..bounding_volumes.len() {
-                    let bounding_volume = bounding_volumes[i].1;
+        for handle in std::mem::take(&mut self.stale_bodies) {
+            if let Some(body) = self.dynamic_bodies.get(handle) {
+                let displacement = &body.as_ref().velocity * elapsed;
 
-                    let volume_mid_point = if is_main_axis_x {
-                        (bounding_volume.top_left.x + bounding_volume.bottom_right.x) / 2.
-                    } else {
-                        (bounding_volume.top_left.y + bounding_volume.bottom_right.y) / 2.
-                    };
+                self.broadphase
+                    .update(handle, &body.to_bounding_volume(), &displacement);
+            }
+        }
+    }
 
-                    if volume_mid_point < overall_mid_point {
-                        bounding_volumes.swap(i, split_index);
-                        split_index += 1;
-                    }
+    /// Updates the broadphase, expecting each body to keep its current velocity for `elapsed`.
+    fn update_broadphase(&mut self, elapsed: f64) {
+        for (handle, body) in self.dynamic_bodies.iter() {
+            let displacement = &body.as_ref().velocity * elapsed;
+
+            self.broadphase
+                .update(handle, &body.to_bounding_volume(), &displacement);
+        }
+
+        self.stale_bodies.clear();
+        self.all_bodies_stale = false;
+    }
+
+    /// Calls `callback` with every dynamic body whose bounding volume may match `is_candidate`.
+    /// `query` finds them in the broadphase, which only knows where the bodies were when it was
+    /// last updated, so the bodies handed out mutably since then are checked directly.
+    fn query_dynamic_bodies(
+        &self,
+        query: impl FnOnce(&dyn Broadphase, &mut dyn FnMut(DynamicBodyHandle)),
+        is_candidate: impl Fn(&BoundingVolume) -> bool,
+        mut callback: impl FnMut(DynamicBodyHandle),
+    ) {
+        let is_candidate = |body: &DynamicBody| is_candidate(&body.to_bounding_volume());
+
+        if self.all_bodies_stale {
+            for (handle, body) in self.dynamic_bodies.iter() {
+                if is_candidate(body) {
+                    callback(handle);
                 }
+            }
 
-                let threshold = 1.max(bounding_volumes.len() / 16);
-                split_index = split_index.clamp(threshold, bounding_volumes.len() - threshold);
+            return;
+        }
 
-                let (left_bounding_volumes, right_bounding_volumes) =
-                    bounding_volumes.split_at_mut(split_index);
+        query(self.broadphase.as_ref(), &mut |handle| {
+            if !self.stale_bodies.contains(&handle) {
+                callback(handle);
+            }
+        });
 
-                Some(Self::Node(
-                    overall_bounding_volume,
-                    Box::new(Self::new(left_bounding_volumes)?),
-                    Box::new(Self::new(right_bounding_volumes)?),
-                ))
+        for &handle in &self.stale_bodies {
+            if self.dynamic_bodies.get(handle).is_some_and(is_candidate) {
+                callback(handle);
             }
         }
     }
 
-    fn get_overlapping_bodies(&self, query_bounding_volume: &BoundingVolume) -> Vec<usize> {
-        let mut result = vec![];
+    fn detect_dynamic_collisions(
+        &mut self,
+    ) -> Vec<(ContactManifold, DynamicBodyHandle, DynamicBodyHandle)> {
+        let mut contacts = vec![];
+
+        self.broadphase.for_each_pair(&mut |i, j| {
+            let (i, j) = (i.min(j), i.max(j));
+
+            if !self.filters.should_collide(i.into(), j.into()) {
+                return;
+            }
+
+            let this = &self.dynamic_bodies[i];
+            let that = &self.dynamic_bodies[j];
+
+            // Sensors do not detect each other.
+            if this.as_ref().is_sensor && that.as_ref().is_sensor {
+                return;
+            }
+
+            if let Some(manifold) = generate_contact_dynamic(this, that) {
+                contacts.push((manifold, i, j));
+            }
+        });
+
+        contacts
+    }
+
+    fn static_tree(&self) -> &StaticTree {
+        self.static_tree
+            .get_or_init(|| StaticTree::new(&self.static_bodies))
+    }
+
+    fn detect_static_collisions(
+        &self,
+    ) -> Vec<(ContactManifold, StaticBodyHandle, usize, DynamicBodyHandle)> {
+        let mut contacts = vec![];
 
-        fn aux(
-            current_node: &BoundingVolumeHierarchyTree,
-            query_bounding_volume: &BoundingVolume,
-            result: &mut Vec<usize>,
-        ) {
-            match current_node {
-                BoundingVolumeHierarchyTree::Leaf(bounding_volume, index) => {
-                    if bounding_volume.is_intersecting(query_bounding_volume) {
-                        result.push(*index);
+        for (j, that) in self.dynamic_bodies.iter() {
+            self.static_tree()
+                .query(&that.to_bounding_volume(), |i, part| {
+                    if !self.filters.should_collide(i.into(), j.into())
+                        || self.static_sensors[i.index()] && that.as_ref().is_sensor
+                    {
+                        return;
                     }
-                }
-                BoundingVolumeHierarchyTree::Node(bounding_volume, left_child, right_child) => {
-                    if bounding_volume.is_intersecting(query_bounding_volume) {
-                        aux(left_child, query_bounding_volume, result);
-                        aux(right_child, query_bounding_volume, result);
+
+                    let this = &self.static_bodies[i];
+
+                    if let Some(manifold) = generate_contact_static(this, part, that) {
+                        contacts.push((manifold, i, part, j));
                     }
-                }
-            }
+                });
         }
 
-        aux(self, query_bounding_volume, &mut result);
+        contacts
+    }
 
-        result
+    /// The sensor and the other body of a pair, if either is a sensor.
+    fn sensor_overlap(
+        &self,
+        this: BodyHandle,
+        that: BodyHandle,
+    ) -> Option<(BodyHandle, BodyHandle)> {
+        if self.is_sensor(this) {
+            Some((this, that))
+        } else if self.is_sensor(that) {
+            Some((that, this))
+        } else {
+            None
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn handle_collisions(&mut self, elapsed: f64) {
+        // Joints may have been changed to invalid bodies through `joint_mut`.
+        let joints: Vec<JointHandle> = self
+            .joints
+            .iter()
+            .filter(|(_, joint)| joint.bodies().are_valid(&self.dynamic_bodies))
+            .map(|(handle, _)| handle)
+            .collect();
 
-    fn create_square(top_left: Vec2D, width: f64) -> BoundingVolume {
-        BoundingVolume {
-            top_left,
-            bottom_right: Vec2D {
-                x: top_left.x + width,
-                y: top_left.y + width,
-            },
+        self.filters.connected = joints
+            .iter()
+            .map(|&handle| self.joints[handle].bodies())
+            .filter(|bodies| !bodies.collide_connected)
+            .filter_map(|bodies| {
+                let this = BodyHandle::from(bodies.this?);
+                let that = BodyHandle::from(bodies.that);
+
+                Some((this.min(that), this.max(that)))
+            })
+            .collect();
+
+        let dynamic_collisions = self.detect_dynamic_collisions();
+
+        let mut contacts = vec![];
+        let mut sensor_overlaps = vec![];
+
+        // Sensors only detect overlaps, so they are kept out of the solver.
+        for (manifold, i, part, j) in self.detect_static_collisions() {
+            match self.sensor_overlap(i.into(), j.into()) {
+                Some(overlap) => sensor_overlaps.push(overlap),
+                None => contacts.push(SolverContact::static_dynamic(
+                    manifold,
+                    i,
+                    part,
+                    j,
+                    &self.static_bodies,
+                    &self.dynamic_bodies,
+                    &self.config,
+                )),
+            }
+        }
+
+        for (manifold, i, j) in dynamic_collisions {
+            match self.sensor_overlap(i.into(), j.into()) {
+                Some(overlap) => sensor_overlaps.push(overlap),
+                None => contacts.push(SolverContact::dynamic(
+                    manifold,
+                    i,
+                    j,
+                    &self.dynamic_bodies,
+                    &self.config,
+                )),
+            }
+        }
+
+        self.sensor_tracker.update(sensor_overlaps);
+
+        for &joint in &joints {
+            self.joints[joint].warm_start(&mut self.dynamic_bodies);
+        }
+
+        for contact in &mut contacts {
+            contact.warm_start(&self.contact_cache, &mut self.dynamic_bodies);
+        }
+
+        // Joints first, so that contacts have the final say.
+        for _ in 0..self.config.velocity_iterations {
+            for &joint in &joints {
+                self.joints[joint].solve_velocities(&mut self.dynamic_bodies, elapsed);
+            }
+
+            for contact in &mut contacts {
+                contact.solve_velocities(&mut self.dynamic_bodies);
+            }
+        }
+
+        self.contact_cache.clear();
+
+        for contact in &contacts {
+            contact.store_impulses(&mut self.contact_cache);
+        }
+
+        self.contact_tracker.update(contacts.iter().map(|contact| {
+            let (this, part) = contact.this();
+
+            (
+                this,
+                part,
+                contact.that(),
+                *contact.manifold(),
+                contact.total_impulse(),
+            )
+        }));
+
+        for _ in 0..self.config.position_iterations {
+            for contact in &contacts {
+                contact.correct_positions(&mut self.dynamic_bodies, &self.config);
+            }
+
+            for &joint in &joints {
+                self.joints[joint]
+                    .correct_positions(&mut self.dynamic_bodies, self.config.baumgarte_factor);
+            }
         }
     }
 
+    /// Whether a body needs continuous collision detection to not tunnel through others during
+    /// the tick. Sensors do not stop, nor are they stopped by anything.
+    fn is_fast(&self, body: &DynamicBody, elapsed: f64) -> bool {
+        let base = body.as_ref();
+
+        !base.is_sensor
+            && (base.is_bullet
+                || base.velocity.length() * elapsed
+                    > self.config.continuous_collision_threshold * body.inner_radius())
+    }
+
+    /// Finds which of the fast bodies would hit something during the tick, along with the
+    /// fraction of the tick after which they do. Sorted by handle, like `fast_bodies`.
+    fn detect_impacts(
+        &self,
+        fast_bodies: &[DynamicBodyHandle],
+        elapsed: f64,
+    ) -> Vec<(DynamicBodyHandle, f64)> {
+        let mut impacts = vec![];
+
+        // How far the other bodies may move, by which the bullets look further in the broadphase.
+        let max_distance = self
+            .dynamic_bodies
+            .values()
+            .map(|body| body.as_ref().velocity.length() * elapsed)
+            .fold(0., f64::max);
+
+        for &i in fast_bodies {
+            let this = &self.dynamic_bodies[i];
+            let is_bullet = this.as_ref().is_bullet;
+            let inner_radius = this.inner_radius();
+            let distance = this.as_ref().velocity.length() * elapsed;
+
+            let swept_bounding_volume = this
+                .to_bounding_volume()
+                .union(&advance(this, elapsed).to_bounding_volume());
+
+            let mut earliest_impact: Option<f64> = None;
+
+            let mut add_impact = |time: Option<f64>| {
+                if let Some(time) = time {
+                    earliest_impact =
+                        Some(earliest_impact.map_or(time, |earliest| earliest.min(time)));
+                }
+            };
+
+            let step = (inner_radius / 2.).max(self.config.slop);
+
+            self.static_tree().query(&swept_bounding_volume, |j, part| {
+                if !self.filters.should_collide(j.into(), i.into())
+                    || self.static_sensors[j.index()]
+                {
+                    return;
+                }
+
+                let that = &self.static_bodies[j];
+
+                add_impact(time_of_impact(distance, step, self.config.slop, |time| {
+                    generate_contact_static(that, part, &advance(this, elapsed * time)).is_some()
+                }));
+            });
+
+            if is_bullet {
+                let bounding_volume = swept_bounding_volume.expand(max_distance);
+
+                self.broadphase.query(&bounding_volume, &mut |j| {
+                    let that = &self.dynamic_bodies[j];
+                    let that_swept_bounding_volume = that
+                        .to_bounding_volume()
+                        .union(&advance(that, elapsed).to_bounding_volume());
+
+                    if i == j
+                        || that.as_ref().is_sensor
+                        || !swept_bounding_volume.is_intersecting(&that_swept_bounding_volume)
+                        || !self.filters.should_collide(i.into(), j.into())
+                    {
+                        return;
+                    }
+
+                    let relative_velocity = &this.as_ref().velocity - &that.as_ref().velocity;
+                    let step = (inner_radius.min(that.inner_radius()) / 2.).max(self.config.slop);
+
+                    add_impact(time_of_impact(
+                        relative_velocity.length() * elapsed,
+                        step,
+                        self.config.slop,
+                        |time| {
+                            generate_contact_dynamic(
+                                &advance(this, elapsed * time),
+                                &advance(that, elapsed * time),
+                            )
+                            .is_some()
+                        },
+                    ));
+                });
+            }
+
+            if let Some(time) = earliest_impact {
+                impacts.push((i, time));
+            }
+        }
+
+        impacts
+    }
+
+    /// Moves the bodies, stopping the fast ones where they first hit something so that they do not
+    /// tunnel through it. The contact is then resolved on the next tick.
+    fn integrate_bodies(&mut self, elapsed: f64) {
+        let fast_bodies: Vec<_> = self
+            .dynamic_bodies
+            .iter()
+            .filter(|(_, body)| self.is_fast(body, elapsed))
+            .map(|(handle, _)| handle)
+            .collect();
+
+        // Bullets find what they may hit in the broadphase, but the solver has moved the bodies
+        // since it was updated.
+        if fast_bodies
+            .iter()
+            .any(|&handle| self.dynamic_bodies[handle].as_ref().is_bullet)
+        {
+            self.update_broadphase(elapsed);
+        }
+
+        let impacts = if fast_bodies.is_empty() {
+            vec![]
+        } else {
+            self.detect_impacts(&fast_bodies, elapsed)
+        };
+        let mut impacts = impacts.into_iter().peekable();
+
+        for (handle, body) in self.dynamic_bodies.iter_mut() {
+            let fraction = impacts
+                .next_if(|(impact_handle, _)| *impact_handle == handle)
+                .map_or(1., |(_, time)| time);
+
+            body.as_mut().integrate(elapsed * fraction);
+        }
+    }
+
+    pub fn tick(&mut self, elapsed: f64) {
+        self.apply_forces();
+        self.apply_gravity(elapsed);
+        // The rest is up to date since the end of the last tick.
+        self.update_stale_bodies(elapsed);
+        self.handle_collisions(elapsed);
+        self.integrate_bodies(elapsed);
+        // For the next tick, and so that queries in between find the bodies where they now are.
+        self.update_broadphase(elapsed);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum BoundingVolumeHierarchyTree<T> {
+    Leaf(BoundingVolume, T),
+    Node(BoundingVolume, Box<Self>, Box<Self>),
+}
+
+impl<T: Copy> BoundingVolumeHierarchyTree<T> {
+    fn new(bounding_volumes: &mut [(T, BoundingVolume)]) -> Option<Self> {
+        match bounding_volumes {
+            [] => None,
+            [(index, bounding_volume)] => {
+                Some(BoundingVolumeHierarchyTree::Leaf(*bounding_volume, *index))
+            }
+            [(_, first_volume), other_volumes @ ..] => {
+                let overall_bounding_volume = other_volumes
+                    .iter()
+                    .fold(*first_volume, |accumulator, (_, bounding_volume)| {
+                        accumulator.union(bounding_volume)
+                    });
+
+                let BoundingVolume {
+                    top_left: Vec2D { x: min_x, y: min_y },
+                    bottom_right: Vec2D { x: max_x, y: max_y },
+                } = overall_bounding_volume;
+
+                let is_main_axis_x = (max_x - min_x) > (max_y - min_y);
+
+                let overall_mid_point = if is_main_axis_x {
+                    (min_x + max_x) / 2.
+                } else {
+                    (min_y + max_y) / 2.
+                };
+
+                let mut split_index = 0;
+
+                for i in 0..bounding_volumes.len() {
+                    let bounding_volume = bounding_volumes[i].1;
+
+                    let volume_mid_point = if is_main_axis_x {
+                        (bounding_volume.top_left.x + bounding_volume.bottom_right.x) / 2.
+                    } else {
+                        (bounding_volume.top_left.y + bounding_volume.bottom_right.y) / 2.
+                    };
+
+                    if volume_mid_point < overall_mid_point {
+                        bounding_volumes.swap(i, split_index);
+                        split_index += 1;
+                    }
+                }
+
+                let threshold = 1.max(bounding_volumes.len() / 16);
+                split_index = split_index.clamp(threshold, bounding_volumes.len() - threshold);
+
+                let (left_bounding_volumes, right_bounding_volumes) =
+                    bounding_volumes.split_at_mut(split_index);
+
+                Some(Self::Node(
+                    overall_bounding_volume,
+                    Box::new(Self::new(left_bounding_volumes)?),
+                    Box::new(Self::new(right_bounding_volumes)?),
+                ))
+            }
+        }
+    }
+
+    fn for_each_overlapping_body(
+        &self,
+        query_bounding_volume: &BoundingVolume,
+        callback: &mut impl FnMut(T),
+    ) {
+        match self {
+            BoundingVolumeHierarchyTree::Leaf(bounding_volume, index) => {
+                if bounding_volume.is_intersecting(query_bounding_volume) {
+                    callback(*index);
+                }
+            }
+            BoundingVolumeHierarchyTree::Node(bounding_volume, left_child, right_child) => {
+                if bounding_volume.is_intersecting(query_bounding_volume) {
+                    left_child.for_each_overlapping_body(query_bounding_volume, callback);
+                    right_child.for_each_overlapping_body(query_bounding_volume, callback);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::joints::*;
+
+    fn create_square(top_left: Vec2D, width: f64) -> BoundingVolume {
+        BoundingVolume {
+            top_left,
+            bottom_right: Vec2D {
+                x: top_left.x + width,
+                y: top_left.y + width,
+            },
+        }
+    }
+
+    #[test]
+    fn test_bodies_moved_between_ticks_collide_where_they_were_moved() {
+        let mut world = World::new(ZERO);
+
+        let create_circle = |x| {
+            DynamicBody::Circle(Circle::new(
+                BaseDynamicBody::new(Vec2D { x, y: 0. }, ZERO, 0., 1.),
+                10.,
+            ))
+        };
+
+        world.add_dynamic(create_circle(0.));
+        let moved = world.add_dynamic(create_circle(500.));
+
+        world.tick(0.01);
+
+        assert!(world.collision_events().is_empty());
+
+        world.get_mut(moved).unwrap().as_mut().position = Vec2D { x: 15., y: 0. };
+        world.tick(0.01);
+
+        assert_eq!(world.collision_events().len(), 1);
+
+        for (_, body) in world.dynamic_bodies_mut() {
+            body.as_mut().position.y += 1000.;
+        }
+
+        world.tick(0.01);
+
+        assert_eq!(
+            world.collision_events()[0].kind,
+            CollisionEventKind::Persist
+        );
+    }
+
+    #[test]
+    fn test_worlds_can_move_between_threads_and_be_shared() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<World>();
+    }
+
+    #[test]
+    fn test_solver_off_centre_contact_produces_spin() {
+        let mut bodies = Arena::new();
+        let mut static_bodies = Arena::new();
+        let floor = static_bodies.insert(StaticBody::Line(Line {
+            normal: UNIT_UP,
+            origin_distance: 10.,
+        }));
+        let rectangle = bodies.insert(DynamicBody::Rectangle(Rectangle::new(
+            BaseDynamicBody::new(ZERO, Vec2D { x: 0., y: 10. }, 0.5, 1.),
+            5.,
+            10.,
+        )));
+
+        let position = Vec2D { x: 5., y: 10. };
+        let manifold = ContactManifold::new(
+            UNIT_UP,
+            &[ContactPoint {
+                position,
+                distance: -1.,
+                id: 0,
+            }],
+        );
+
+        let mut contact = SolverContact::static_dynamic(
+            manifold,
+            floor,
+            0,
+            rectangle,
+            &static_bodies,
+            &bodies,
+            &WorldConfig::default(),
+        );
+        contact.solve_velocities(&mut bodies);
+
+        let body = bodies[rectangle].as_ref();
+
+        assert!(body.velocity.y < 10.);
+        assert!(body.angular_velocity < 0.);
+
+        let contact_velocity = body.velocity_at(&(&position - &body.position));
+
+        assert!((contact_velocity.y + 0.5 * 10.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_warm_starting_reuses_the_previous_impulses() {
+        let (mut world, handle) = create_slope_world(0.6);
+
+        for _ in 0..10 {
+            world.tick(0.01);
+        }
+
+        let cached_points = world.contact_cache.len();
+        assert!(cached_points > 0);
+
+        let mut cold_world = world.clone();
+        cold_world.contact_cache.clear();
+
+        world.tick(0.01);
+        cold_world.tick(0.01);
+
+        assert_eq!(world.contact_cache.len(), cached_points);
+
+        // With a head start, the solver stops the box sliding sooner.
+        let velocity = world.dynamic_bodies[handle].as_ref().velocity.length();
+        let cold_velocity = cold_world.dynamic_bodies[handle].as_ref().velocity.length();
+        assert!(velocity <= cold_velocity);
+    }
+
+    fn create_slope_world(friction: f64) -> (World, DynamicBodyHandle) {
+        let angle: f64 = 20f64.to_radians();
+        let normal = Vec2D {
+            x: angle.sin(),
+            y: -angle.cos(),
+        };
+        let origin = Vec2D { x: 0., y: 100. };
+
+        let mut body = BaseDynamicBody::new(&origin + &(&normal * 5.), ZERO, 0., 1.);
+        body.orientation = angle;
+        body.static_friction = friction;
+        body.dynamic_friction = friction;
+
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        world.add_static(StaticBody::Line(Line {
+            normal,
+            origin_distance: -normal.dot_product(&origin),
+        }));
+        let handle = world.add_dynamic(DynamicBody::Rectangle(Rectangle::new(body, 5., 5.)));
+
+        (world, handle)
+    }
+
+    #[test]
+    fn test_friction_keeps_a_box_at_rest_on_a_slope() {
+        let (mut world, handle) = create_slope_world(0.6);
+        let initial_position = world.dynamic_bodies[handle].as_ref().position;
+
+        for _ in 0..200 {
+            world.tick(0.01);
+        }
+
+        let body = world.dynamic_bodies[handle].as_ref();
+
+        assert!((&body.position - &initial_position).length() < 1.);
+        assert!(body.velocity.length() < 1.);
+    }
+
+    #[test]
+    fn test_a_box_slides_down_a_slope_without_friction() {
+        let (mut world, handle) = create_slope_world(0.);
+        let initial_position = world.dynamic_bodies[handle].as_ref().position;
+
+        for _ in 0..200 {
+            world.tick(0.01);
+        }
+
+        let body = world.dynamic_bodies[handle].as_ref();
+
+        assert!((&body.position - &initial_position).length() > 10.);
+    }
+
+    #[test]
+    fn test_more_position_iterations_resolve_more_overlap() {
+        let overlap_after_tick = |position_iterations| {
+            let config = WorldConfig {
+                position_iterations,
+                ..WorldConfig::default()
+            };
+            let mut world = World::with_config(ZERO, config);
+
+            world.add_static(StaticBody::Line(Line {
+                normal: UNIT_UP,
+                origin_distance: 100.,
+            }));
+            let circle = world.add_dynamic(DynamicBody::Circle(Circle::new(
+                BaseDynamicBody::new(Vec2D { x: 0., y: 95. }, ZERO, 0., 1.),
+                10.,
+            )));
+
+            world.tick(0.01);
+
+            world.get(circle).unwrap().as_ref().position.y - 90.
+        };
+
+        let overlap = overlap_after_tick(1);
+        let iterated_overlap = overlap_after_tick(10);
+
+        assert!(overlap > 0.);
+        assert!(iterated_overlap < overlap);
+        assert!(iterated_overlap < WorldConfig::default().slop + 0.1);
+    }
+
+    #[test]
+    fn test_a_box_slides_smoothly_across_a_chain() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let vertices = (-2..=10)
+            .map(|i| Vec2D {
+                x: 10. * i as f64,
+                y: 0.,
+            })
+            .collect();
+        world.add_static(StaticBody::Chain(Chain::new(vertices, false).unwrap()));
+
+        let mut body =
+            BaseDynamicBody::new(Vec2D { x: 0., y: -5. }, Vec2D { x: 50., y: 0. }, 0., 1.);
+        body.static_friction = 0.;
+        body.dynamic_friction = 0.;
+        let rectangle = world.add_dynamic(DynamicBody::Rectangle(Rectangle::new(body, 5., 5.)));
+
+        for _ in 0..100 {
+            world.tick(0.01);
+        }
+
+        let body = world.get(rectangle).unwrap().as_ref();
+
+        assert!((body.position.x - 50.).abs() < 1.);
+        assert!((body.velocity.x - 50.).abs() < 1.);
+    }
+
+    #[test]
+    fn test_a_circle_rests_on_a_static_rectangle() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let mut pillar_body = BaseDynamicBody::new(Vec2D { x: 0., y: 100. }, ZERO, 0., 0.);
+        pillar_body.orientation = 0.1;
+        let pillar = world.add_static(StaticBody::Rectangle(Rectangle::new(pillar_body, 50., 10.)));
+
+        let circle = world.add_dynamic(DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(Vec2D { x: 0., y: 50. }, ZERO, 0., 1.),
+            5.,
+        )));
+
+        for _ in 0..300 {
+            world.tick(0.01);
+        }
+
+        let Some(StaticBody::Rectangle(pillar)) = world.get(pillar) else {
+            panic!("the pillar should still be there");
+        };
+        assert_eq!(pillar.body.position, Vec2D { x: 0., y: 100. });
+        assert_eq!(pillar.body.orientation, 0.1);
+
+        // The circle rests on the top face, at most as far as the slop into it.
+        let circle = world.get(circle).unwrap().as_ref();
+        let height_above_face = UNIT_UP
+            .rotate(0.1)
+            .dot_product(&(&circle.position - &pillar.body.position));
+        assert!((height_above_face - 15.).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_a_static_peg_deflects_a_falling_circle() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        world.add_static(StaticBody::Circle(Circle::new(
+            BaseDynamicBody::new(Vec2D { x: 0., y: 100. }, ZERO, 0., 0.),
+            5.,
+        )));
+        let circle = world.add_dynamic(DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(Vec2D { x: 1., y: 50. }, ZERO, 0.5, 1.),
+            5.,
+        )));
+
+        for _ in 0..150 {
+            world.tick(0.01);
+        }
+
+        let circle = world.get(circle).unwrap().as_ref();
+
+        assert!(circle.velocity.x > 0.);
+        assert!(circle.position.x > 5.);
+    }
+
+    #[test]
+    fn test_solid_static_bodies_use_their_own_material() {
+        let rebound_velocity = |coefficient_of_restitution| {
+            let mut world = World::new(ZERO);
+
+            world.add_static(StaticBody::Circle(Circle::new(
+                BaseDynamicBody::new(ZERO, ZERO, coefficient_of_restitution, 0.),
+                10.,
+            )));
+            let circle = world.add_dynamic(DynamicBody::Circle(Circle::new(
+                BaseDynamicBody::new(Vec2D { x: 0., y: -20. }, Vec2D { x: 0., y: 100. }, 1., 1.),
+                5.,
+            )));
+
+            for _ in 0..20 {
+                world.tick(0.01);
+            }
+
+            world.get(circle).unwrap().as_ref().velocity.y
+        };
+
+        assert!(rebound_velocity(1.) < -90.);
+        assert!(rebound_velocity(0.).abs() < 1.);
+    }
+
+    #[test]
+    fn test_static_bodies_can_be_changed_between_ticks() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let circle = world.add_dynamic(DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(ZERO, ZERO, 0., 1.),
+            5.,
+        )));
+        world.tick(0.01);
+
+        let floor = world.add_static(StaticBody::Segment(
+            Segment::new(Vec2D { x: -50., y: 20. }, Vec2D { x: 50., y: 20. }, false).unwrap(),
+        ));
+
+        for _ in 0..100 {
+            world.tick(0.01);
+        }
+
+        assert!((world.get(circle).unwrap().as_ref().position.y - 15.).abs() < 0.1);
+
+        if let Some(StaticBody::Segment(segment)) = world.get_mut(floor) {
+            *segment =
+                Segment::new(Vec2D { x: -50., y: 100. }, Vec2D { x: 50., y: 100. }, false).unwrap();
+        }
+
+        for _ in 0..200 {
+            world.tick(0.01);
+        }
+
+        assert!((world.get(circle).unwrap().as_ref().position.y - 95.).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_handles_stay_valid_across_removals() {
+        let mut world = World::new(ZERO);
+
+        let bodies: Vec<_> = (0..3)
+            .map(|i| {
+                let position = Vec2D {
+                    x: 100. * i as f64,
+                    y: 0.,
+                };
+
+                world.add_dynamic(DynamicBody::Circle(Circle::new(
+                    BaseDynamicBody::new(position, ZERO, 0., 1.),
+                    10.,
+                )))
+            })
+            .collect();
+
+        assert_eq!(
+            world.remove(bodies[1]).map(|body| body.as_ref().position.x),
+            Some(100.)
+        );
+        assert!(world.get(bodies[1]).is_none());
+
+        let replacement = world.add_dynamic(DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(ZERO, ZERO, 0., 1.),
+            10.,
+        )));
+
+        assert_ne!(replacement, bodies[1]);
+        assert!(world.get(bodies[1]).is_none());
+        assert_eq!(world.get(bodies[2]).unwrap().as_ref().position.x, 200.);
+        assert_eq!(world.dynamic_body_count(), 3);
+
+        world.tick(0.01);
+    }
+
+    #[test]
+    fn test_broadphases_follow_moved_and_removed_bodies() {
+        for broadphase in [
+            Box::new(TreeBroadphase::new()) as Box<dyn Broadphase>,
+            Box::new(SweepAndPrune::new()),
+            Box::new(SpatialHash::new(20.)),
+        ] {
+            check_broadphase_follows_moved_and_removed_bodies(broadphase);
+        }
+    }
+
+    fn check_broadphase_follows_moved_and_removed_bodies(broadphase: Box<dyn Broadphase>) {
+        let mut world = World::new(ZERO);
+        world.broadphase = broadphase;
+
+        let create_circle = |x| {
+            DynamicBody::Circle(Circle::new(
+                BaseDynamicBody::new(Vec2D { x, y: 0. }, ZERO, 0., 1.),
+                10.,
+            ))
+        };
+
+        let this = world.add_dynamic(create_circle(0.));
+        let that = world.add_dynamic(create_circle(100.));
+        let removed = world.add_dynamic(create_circle(200.));
+
+        world.tick(0.01);
+        world.remove(removed);
+
+        // Teleport a body far outside of its fat bounding volume, onto the other one.
+        world.get_mut(this).unwrap().as_mut().position = Vec2D { x: 90., y: 0. };
+        world.tick(0.01);
+
+        // The overlap was found, so the bodies were pushed apart.
+        assert!(world.get(this).unwrap().as_ref().position.x < 90.);
+        assert!(world.get(that).unwrap().as_ref().position.x > 100.);
+    }
+
+    #[test]
+    fn test_a_fast_circle_does_not_tunnel_through_a_segment() {
+        let mut world = World::new(ZERO);
+
+        world.add_static(StaticBody::Segment(
+            Segment::new(Vec2D { x: -50., y: 50. }, Vec2D { x: 50., y: 50. }, true).unwrap(),
+        ));
+
+        let circle = world.add_dynamic(DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(ZERO, Vec2D { x: 0., y: 1000. }, 0., 1.),
+            1.,
+        )));
+
+        for _ in 0..10 {
+            world.tick(0.1);
+        }
+
+        let circle = world.get(circle).unwrap().as_ref();
+        assert!(circle.position.y < 50.);
+        assert!(circle.velocity.y <= 0.);
+    }
+
+    #[test]
+    fn test_a_bullet_does_not_tunnel_through_a_dynamic_body() {
+        let mut world = World::new(ZERO);
+
+        let wall = world.add_dynamic(DynamicBody::Rectangle(Rectangle::new(
+            BaseDynamicBody::new(Vec2D { x: 50., y: 0. }, ZERO, 0., 1.),
+            1.,
+            50.,
+        )));
+
+        let mut body = BaseDynamicBody::new(ZERO, Vec2D { x: 1000., y: 0. }, 0., 1.);
+        body.is_bullet = true;
+        let bullet = world.add_dynamic(DynamicBody::Circle(Circle::new(body, 1.)));
+
+        for _ in 0..3 {
+            world.tick(0.1);
+        }
+
+        let wall = world.get(wall).unwrap().as_ref().position.x;
+        let bullet = world.get(bullet).unwrap().as_ref().position.x;
+        assert!(bullet < wall);
+    }
+
+    #[test]
+    fn test_a_bullet_does_not_tunnel_through_a_body_coming_towards_it() {
+        let mut world = World::new(ZERO);
+        // Which, unlike the tree, does not grow the bounding volumes by the motion of the bodies.
+        world.set_broadphase(SweepAndPrune::new());
+
+        // Out of reach of the bullet until it moves.
+        let wall = world.add_dynamic(DynamicBody::Rectangle(Rectangle::new(
+            BaseDynamicBody::new(Vec2D { x: 103., y: 0. }, Vec2D { x: -50., y: 0. }, 0., 1.),
+            1.,
+            50.,
+        )));
+
+        let mut body = BaseDynamicBody::new(ZERO, Vec2D { x: 1000., y: 0. }, 0., 1.);
+        body.is_bullet = true;
+        let bullet = world.add_dynamic(DynamicBody::Circle(Circle::new(body, 1.)));
+
+        world.tick(0.1);
+
+        let wall = world.get(wall).unwrap().as_ref().position.x;
+        let bullet = world.get(bullet).unwrap().as_ref().position.x;
+        assert!(bullet < wall);
+    }
+
+    #[test]
+    fn test_a_bullet_at_an_extreme_velocity_stops_at_the_floor() {
+        let mut world = World::new(ZERO);
+
+        world.add_static(StaticBody::Line(Line {
+            normal: UNIT_UP,
+            origin_distance: 100.,
+        }));
+
+        let mut body = BaseDynamicBody::new(ZERO, Vec2D { x: 0., y: 1e12 }, 0., 1.);
+        body.is_bullet = true;
+        let bullet = world.add_dynamic(DynamicBody::Circle(Circle::new(body, 1.)));
+
+        world.tick(0.1);
+
+        assert!(world.get(bullet).unwrap().as_ref().position.y < 100.);
+    }
+
+    /// Two circles falling onto the same spot of a floor, one on top of the other.
+    fn create_stack_world() -> (World, StaticBodyHandle, [DynamicBodyHandle; 2]) {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let floor = world.add_static(StaticBody::Line(Line {
+            normal: UNIT_UP,
+            origin_distance: 100.,
+        }));
+
+        let [bottom, top] = [80., 50.].map(|y| {
+            world.add_dynamic(DynamicBody::Circle(Circle::new(
+                BaseDynamicBody::new(Vec2D { x: 0., y }, ZERO, 0., 1.),
+                10.,
+            )))
+        });
+
+        (world, floor, [bottom, top])
+    }
+
+    fn run_stack_world(world: &mut World, circles: [DynamicBodyHandle; 2]) -> [f64; 2] {
+        for _ in 0..200 {
+            world.tick(0.01);
+        }
+
+        circles.map(|circle| world.get(circle).unwrap().as_ref().position.y)
+    }
+
+    #[test]
+    fn test_debris_passes_through_debris_but_not_the_floor() {
+        const DEBRIS: u32 = 1 << 1;
+
+        let (mut world, _, circles) = create_stack_world();
+
+        for circle in circles {
+            *world.collision_filter_mut(circle).unwrap() = CollisionFilter {
+                category: DEBRIS,
+                mask: !DEBRIS,
+                group: 0,
+            };
+        }
+
+        let [bottom, top] = run_stack_world(&mut world, circles);
+
+        assert!((bottom - 90.).abs() < 0.1);
+        assert!((top - 90.).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_negative_groups_and_the_pair_filter_stop_collisions() {
+        let (mut world, _, circles) = create_stack_world();
+        let [stacked_bottom, stacked_top] = run_stack_world(&mut world, circles);
+
+        assert!((stacked_bottom - 90.).abs() < 0.1);
+        assert!((stacked_top - 70.).abs() < 0.2);
+
+        let (mut world, _, circles) = create_stack_world();
+
+        for circle in circles {
+            world.collision_filter_mut(circle).unwrap().group = -1;
+        }
+
+        assert!((run_stack_world(&mut world, circles)[1] - 90.).abs() < 0.1);
+
+        let (mut world, floor, circles) = create_stack_world();
+        world.set_pair_filter(move |this, that| this != floor.into() && that != floor.into());
+
+        assert!(run_stack_world(&mut world, circles)[0] > 100.);
+    }
+
+    #[test]
+    fn test_collision_events_begin_persist_and_end() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let floor = world.add_static(StaticBody::Line(Line {
+            normal: UNIT_UP,
+            origin_distance: 100.,
+        }));
+        let circle = world.add_dynamic(DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(Vec2D { x: 0., y: 85. }, ZERO, 0., 1.),
+            10.,
+        )));
+
+        while world.collision_events().is_empty() {
+            world.tick(0.01);
+        }
+
+        let [begin] = world.collision_events() else {
+            panic!("expected a single event");
+        };
+
+        assert_eq!(begin.kind, CollisionEventKind::Begin);
+        assert_eq!(begin.this, BodyHandle::Static(floor));
+        assert_eq!(begin.that, circle);
+        assert_eq!(begin.manifold.normal, UNIT_UP);
+        assert!(begin.penetration() > 0.);
+        // The floor pushes the circle up.
+        assert!(begin.impulse.y < 0.);
+
+        world.tick(0.01);
+
+        assert_eq!(world.collision_events().len(), 1);
+        assert_eq!(
+            world.collision_events()[0].kind,
+            CollisionEventKind::Persist
+        );
+
+        world.remove(circle);
+        world.tick(0.01);
+
+        let [end] = world.collision_events() else {
+            panic!("expected a single event");
+        };
+
+        assert_eq!(end.kind, CollisionEventKind::End);
+        assert_eq!(end.that, circle);
+        assert_eq!(end.impulse, ZERO);
+
+        world.tick(0.01);
+
+        assert!(world.collision_events().is_empty());
+    }
+
+    #[test]
+    fn test_collision_events_between_dynamic_bodies() {
+        let mut world = World::new(ZERO);
+
+        // Bouncy, so that the circles separate again.
+        let create_circle = |position, velocity| {
+            let mut body = BaseDynamicBody::new(position, velocity, 0., 1.);
+            body.coefficient_of_restitution = 1.;
+
+            DynamicBody::Circle(Circle::new(body, 10.))
+        };
+
+        let left = world.add_dynamic(create_circle(ZERO, Vec2D { x: 100., y: 0. }));
+        let right = world.add_dynamic(create_circle(Vec2D { x: 25., y: 0. }, ZERO));
+
+        let mut kinds = vec![];
+
+        for _ in 0..100 {
+            world.tick(0.01);
+
+            for event in world.collision_events() {
+                assert_eq!(event.this, BodyHandle::Dynamic(left));
+                assert_eq!(event.that, right);
+
+                if event.kind == CollisionEventKind::Begin {
+                    // The left circle pushes the right one along.
+                    assert!(event.impulse.x > 0.);
+                }
+
+                kinds.push(event.kind);
+            }
+        }
+
+        assert_eq!(kinds.first(), Some(&CollisionEventKind::Begin));
+        assert_eq!(kinds.last(), Some(&CollisionEventKind::End));
+    }
+
+    #[test]
+    fn test_a_body_passes_through_a_static_sensor() {
+        let mut world = World::new(ZERO);
+
+        let goal = world.add_static_sensor(StaticBody::Rectangle(Rectangle::new(
+            BaseDynamicBody::new(Vec2D { x: 50., y: 0. }, ZERO, 0., 0.),
+            10.,
+            10.,
+        )));
+        let circle = world.add_dynamic(DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(ZERO, Vec2D { x: 100., y: 0. }, 0., 1.),
+            5.,
+        )));
+
+        let mut events = vec![];
+
+        for _ in 0..100 {
+            world.tick(0.01);
+            events.extend_from_slice(world.sensor_events());
+        }
+
+        let expected = [SensorEventKind::Enter, SensorEventKind::Exit].map(|kind| SensorEvent {
+            kind,
+            sensor: goal.into(),
+            other: circle.into(),
+        });
+
+        assert_eq!(events, expected);
+        assert_eq!(world.get(circle).unwrap().as_ref().velocity.x, 100.);
+        assert!(world.collision_events().is_empty());
+    }
+
+    #[test]
+    fn test_dynamic_sensors_detect_bodies_but_not_each_other() {
+        let mut world = World::new(ZERO);
+
+        let create_pickup = |x| {
+            let mut body = BaseDynamicBody::new(Vec2D { x, y: 0. }, ZERO, 0., 1.);
+            body.is_sensor = true;
+
+            DynamicBody::Circle(Circle::new(body, 5.))
+        };
+
+        let pickup = world.add_dynamic(create_pickup(30.));
+        world.add_dynamic(create_pickup(35.));
+        let circle = world.add_dynamic(DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(ZERO, Vec2D { x: 100., y: 0. }, 0., 1.),
+            5.,
+        )));
+
+        let mut entered = vec![];
+
+        for _ in 0..50 {
+            world.tick(0.01);
+
+            for event in world.sensor_events() {
+                assert_eq!(event.other, circle.into());
+
+                if event.kind == SensorEventKind::Enter {
+                    entered.push(event.sensor);
+                }
+            }
+        }
+
+        assert_eq!(entered.len(), 2);
+        assert!(entered.contains(&pickup.into()));
+        assert!(world.is_sensor(pickup.into()));
+        assert!(!world.is_sensor(circle.into()));
+
+        // Neither the circle nor the pickup were pushed.
+        assert_eq!(world.get(circle).unwrap().as_ref().velocity.x, 100.);
+        assert_eq!(world.get(pickup).unwrap().as_ref().position.x, 30.);
+    }
+
+    #[test]
+    fn test_jointed_bodies_do_not_collide_and_lose_their_joints_with_them() {
+        let mut world = World::new(ZERO);
+
+        let create_circle = |x| {
+            DynamicBody::Circle(Circle::new(
+                BaseDynamicBody::new(Vec2D { x, y: 0. }, ZERO, 0., 1.),
+                10.,
+            ))
+        };
+
+        let this = world.add_dynamic(create_circle(0.));
+        let that = world.add_dynamic(create_circle(10.));
+        let other = world.add_dynamic(create_circle(100.));
+
+        let joint = world
+            .add_joint(RevoluteJoint::new(JointBodies::new(
+                this,
+                Vec2D { x: 5., y: 0. },
+                that,
+                Vec2D { x: -5., y: 0. },
+            )))
+            .unwrap();
+
+        assert!(
+            world
+                .add_joint(DistanceJoint::new(
+                    JointBodies::new(this, ZERO, this, ZERO),
+                    1.
+                ))
+                .is_none()
+        );
+
+        world.tick(0.01);
+
+        assert!(world.collision_events().is_empty());
+        assert_eq!(world.get(that).unwrap().as_ref().position.x, 10.);
+
+        world.remove(this);
+
+        assert!(world.joint(joint).is_none());
+        assert!(
+            world
+                .add_joint(DistanceJoint::new(
+                    JointBodies::new(this, ZERO, other, ZERO),
+                    1.
+                ))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_joints_retargeted_to_invalid_bodies_are_skipped() {
+        let mut world = World::new(Vec2D { x: 0., y: 100. });
+
+        let create_circle = |x| {
+            DynamicBody::Circle(Circle::new(
+                BaseDynamicBody::new(Vec2D { x, y: 0. }, ZERO, 0., 1.),
+                1.,
+            ))
+        };
+
+        let weight = world.add_dynamic(create_circle(0.));
+        let removed = world.add_dynamic(create_circle(100.));
+        world.remove(removed);
+
+        let joint = world
+            .add_joint(DistanceJoint::new(
+                JointBodies::to_world(Vec2D { x: 0., y: -10. }, weight, ZERO),
+                10.,
+            ))
+            .unwrap();
+
+        let Joint::Distance(distance) = world.joint_mut(joint).unwrap() else {
+            unreachable!();
+        };
+        distance.bodies.that = removed;
+
+        // The weight falls freely.
+        world.tick(0.1);
+
+        assert!((world.get(weight).unwrap().as_ref().velocity.y - 10.).abs() < 1e-9);
+
+        let Joint::Distance(distance) = world.joint_mut(joint).unwrap() else {
+            unreachable!();
+        };
+        distance.bodies.that = weight;
+
+        world.tick(0.1);
+
+        assert!(world.get(weight).unwrap().as_ref().velocity.y < 10.);
+    }
+
     #[test]
     fn test_bvh_new() {
         let bv1 = create_square(ZERO, 10.);
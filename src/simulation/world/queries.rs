@@ -0,0 +1,497 @@
+use crate::body::{DynamicBody, StaticBody};
+use crate::bounding_volume::BoundingVolume;
+use crate::simulation::collisions::*;
+use crate::vec2::Vec2D;
+
+use super::continuous::time_of_impact;
+use super::{BodyHandle, World};
+
+/// Where a ray or a swept shape first hits a body.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CastHit {
+    pub body: BodyHandle,
+    pub point: Vec2D,
+    /// The normal of the hit body's surface at `point`, pointing out of it.
+    pub normal: Vec2D,
+    /// How far along the cast the hit is, from 0 at the start to 1 at the end.
+    pub fraction: f64,
+}
+
+fn translate(body: &DynamicBody, translation: &Vec2D) -> DynamicBody {
+    let mut body = *body;
+    body.as_mut().position += translation;
+    body
+}
+
+impl World {
+    /// The static bodies with a matching part, followed by the matching dynamic bodies, among
+    /// those that may intersect `bounding_volume`.
+    fn query_bodies(
+        &self,
+        bounding_volume: &BoundingVolume,
+        mut is_static_matching: impl FnMut(&StaticBody, usize) -> bool,
+        mut is_dynamic_matching: impl FnMut(&DynamicBody) -> bool,
+    ) -> Vec<BodyHandle> {
+        let mut static_bodies = vec![];
+
+        self.static_tree().query(bounding_volume, |handle, part| {
+            if is_static_matching(&self.static_bodies[handle], part) {
+                static_bodies.push(handle);
+            }
+        });
+
+        // A body is listed once for each matching part.
+        static_bodies.sort_unstable();
+        static_bodies.dedup();
+
+        let mut bodies: Vec<BodyHandle> = static_bodies.into_iter().map(Into::into).collect();
+
+        self.query_dynamic_bodies(
+            |broadphase, callback| broadphase.query(bounding_volume, callback),
+            |body_bounding_volume| body_bounding_volume.is_intersecting(bounding_volume),
+            |handle| {
+                if is_dynamic_matching(&self.dynamic_bodies[handle]) {
+                    bodies.push(handle.into());
+                }
+            },
+        );
+
+        bodies
+    }
+
+    /// The bodies containing `point`. Segments and chains have no area, so are never included.
+    pub fn bodies_at_point(&self, point: Vec2D) -> Vec<BodyHandle> {
+        let bounding_volume = BoundingVolume {
+            top_left: point,
+            bottom_right: point,
+        };
+
+        self.query_bodies(
+            &bounding_volume,
+            |body, part| contains_point_static(body, part, &point),
+            |body| contains_point_dynamic(body, &point),
+        )
+    }
+
+    /// The bodies whose bounding volume intersects `bounding_volume`. For infinite lines, these
+    /// are the ones reaching into `bounding_volume`.
+    pub fn bodies_in_aabb(&self, bounding_volume: BoundingVolume) -> Vec<BodyHandle> {
+        let BoundingVolume {
+            top_left,
+            bottom_right,
+        } = bounding_volume;
+
+        let corners = [
+            top_left,
+            Vec2D {
+                x: bottom_right.x,
+                y: top_left.y,
+            },
+            bottom_right,
+            Vec2D {
+                x: top_left.x,
+                y: bottom_right.y,
+            },
+        ];
+
+        self.query_bodies(
+            &bounding_volume,
+            |body, part| match body.part_bounding_volume(part) {
+                Some(part_bounding_volume) => {
+                    part_bounding_volume.is_intersecting(&bounding_volume)
+                }
+                None => corners
+                    .iter()
+                    .any(|corner| contains_point_static(body, part, corner)),
+            },
+            |body| body.to_bounding_volume().is_intersecting(&bounding_volume),
+        )
+    }
+
+    /// The bodies overlapping `shape`, according to the same contact routines as the simulation.
+    /// Only the shape and position of `shape` matter.
+    pub fn bodies_overlapping_shape(&self, shape: &DynamicBody) -> Vec<BodyHandle> {
+        self.query_bodies(
+            &shape.to_bounding_volume(),
+            |body, part| generate_contact_static(body, part, shape).is_some(),
+            |body| generate_contact_dynamic(body, shape).is_some(),
+        )
+    }
+
+    /// Finds the first body hit by a ray from `origin` towards `direction`, up to `max_distance`
+    /// away, skipping the bodies for which `filter` returns `false`.
+    ///
+    /// Bodies containing `origin` are not hit. Returns `None` if `direction` is zero.
+    pub fn raycast(
+        &self,
+        origin: Vec2D,
+        direction: Vec2D,
+        max_distance: f64,
+        mut filter: impl FnMut(BodyHandle) -> bool,
+    ) -> Option<CastHit> {
+        let ray = Ray::new(origin, direction, max_distance)?;
+
+        let mut closest_hit: Option<(BodyHandle, RayHit)> = None;
+
+        let mut add_hit = |handle: BodyHandle, hit: Option<RayHit>| {
+            if let Some(hit) = hit
+                && closest_hit.is_none_or(|(_, closest)| hit.distance < closest.distance)
+                && filter(handle)
+            {
+                closest_hit = Some((handle, hit));
+            }
+        };
+
+        self.static_tree()
+            .query(&ray.to_bounding_volume(), |handle, part| {
+                add_hit(
+                    handle.into(),
+                    raycast_static(&self.static_bodies[handle], part, &ray),
+                );
+            });
+
+        self.query_dynamic_bodies(
+            |broadphase, callback| broadphase.query_segment(&ray.origin, &ray.end(), callback),
+            |body_bounding_volume| {
+                body_bounding_volume.is_intersecting_segment(&ray.origin, &ray.end())
+            },
+            |handle| {
+                add_hit(
+                    handle.into(),
+                    raycast_dynamic(&self.dynamic_bodies[handle], &ray),
+                );
+            },
+        );
+
+        closest_hit.map(|(body, hit)| CastHit {
+            body,
+            point: ray.point_at(hit.distance),
+            normal: hit.normal,
+            fraction: if max_distance > 0. {
+                hit.distance / max_distance
+            } else {
+                0.
+            },
+        })
+    }
+
+    /// Sweeps `shape` by `translation`, without rotating it, and finds the first body it hits,
+    /// skipping the bodies for which `filter` returns `false`.
+    ///
+    /// Only the shape and position of `shape` matter. Bodies it overlaps from the start are not
+    /// hit, and at the hit, it may overlap the body by up to the slop of the world's config.
+    pub fn cast_shape(
+        &self,
+        shape: &DynamicBody,
+        translation: Vec2D,
+        mut filter: impl FnMut(BodyHandle) -> bool,
+    ) -> Option<CastHit> {
+        let distance = translation.length();
+        let inner_radius = shape.inner_radius();
+        let tolerance = self.config.slop;
+
+        let swept_bounding_volume = shape
+            .to_bounding_volume()
+            .union(&translate(shape, &translation).to_bounding_volume());
+
+        let at = |fraction: f64| translate(shape, &(&translation * fraction));
+
+        // The part is that of the hit static body, or 0 for dynamic bodies.
+        let mut closest_hit: Option<(BodyHandle, usize, f64)> = None;
+
+        let mut add_hit =
+            |handle: BodyHandle, part: usize, step: f64, is_overlapping: &dyn Fn(f64) -> bool| {
+                if !filter(handle) {
+                    return;
+                }
+
+                let Some(fraction) =
+                    time_of_impact(distance, step.max(tolerance), tolerance, is_overlapping)
+                else {
+                    return;
+                };
+
+                if closest_hit.is_none_or(|(_, _, closest)| fraction < closest) {
+                    closest_hit = Some((handle, part, fraction));
+                }
+            };
+
+        self.static_tree()
+            .query(&swept_bounding_volume, |handle, part| {
+                let body = &self.static_bodies[handle];
+
+                add_hit(handle.into(), part, inner_radius / 2., &|fraction| {
+                    generate_contact_static(body, part, &at(fraction)).is_some()
+                });
+            });
+
+        self.query_dynamic_bodies(
+            |broadphase, callback| broadphase.query(&swept_bounding_volume, callback),
+            |body_bounding_volume| body_bounding_volume.is_intersecting(&swept_bounding_volume),
+            |handle| {
+                let body = &self.dynamic_bodies[handle];
+                let step = inner_radius.min(body.inner_radius()) / 2.;
+
+                add_hit(handle.into(), 0, step, &|fraction| {
+                    generate_contact_dynamic(body, &at(fraction)).is_some()
+                });
+            },
+        );
+
+        let (body, part, fraction) = closest_hit?;
+
+        // The contacts go from the hit body to the shape, so the normal points out of the body.
+        let manifold = match body {
+            BodyHandle::Static(handle) => {
+                generate_contact_static(&self.static_bodies[handle], part, &at(fraction))
+            }
+            BodyHandle::Dynamic(handle) => {
+                generate_contact_dynamic(&self.dynamic_bodies[handle], &at(fraction))
+            }
+        }?;
+
+        Some(CastHit {
+            body,
+            point: manifold.deepest_point().position,
+            normal: manifold.normal,
+            fraction,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::body::*;
+    use crate::vec2::*;
+
+    use super::*;
+
+    fn create_world() -> World {
+        let mut world = World::new(ZERO);
+
+        world.add_static(StaticBody::Line(Line {
+            normal: UNIT_UP,
+            origin_distance: 100.,
+        }));
+
+        world
+    }
+
+    fn create_circle(position: Vec2D, radius: f64) -> DynamicBody {
+        DynamicBody::Circle(Circle::new(
+            BaseDynamicBody::new(position, ZERO, 0., 1.),
+            radius,
+        ))
+    }
+
+    #[test]
+    fn test_bodies_at_point_picks_the_bodies_under_the_point() {
+        let mut world = create_world();
+
+        let circle = world.add_dynamic(create_circle(Vec2D { x: 0., y: 50. }, 10.));
+        world.add_dynamic(create_circle(Vec2D { x: 30., y: 50. }, 10.));
+
+        assert_eq!(
+            world.bodies_at_point(Vec2D { x: 5., y: 55. }),
+            vec![circle.into()]
+        );
+        assert_eq!(world.bodies_at_point(Vec2D { x: 15., y: 50. }), vec![]);
+        assert_eq!(world.bodies_at_point(Vec2D { x: 0., y: 150. }).len(), 1);
+    }
+
+    #[test]
+    fn test_bodies_in_aabb_and_overlapping_shape() {
+        let mut world = create_world();
+
+        let chain = world.add_static(StaticBody::Chain(
+            Chain::new(
+                vec![
+                    Vec2D { x: -50., y: 0. },
+                    Vec2D { x: 0., y: 10. },
+                    Vec2D { x: 50., y: 0. },
+                ],
+                false,
+            )
+            .unwrap(),
+        ));
+        let near = world.add_dynamic(create_circle(Vec2D { x: 0., y: 50. }, 10.));
+        let far = world.add_dynamic(create_circle(Vec2D { x: 80., y: 80. }, 10.));
+
+        let in_aabb = world.bodies_in_aabb(BoundingVolume {
+            top_left: Vec2D { x: -10., y: -10. },
+            bottom_right: Vec2D { x: 10., y: 45. },
+        });
+
+        assert_eq!(in_aabb, vec![chain.into(), near.into()]);
+
+        // An explosion, reaching the floor and the far circle, but not the others.
+        let explosion = create_circle(Vec2D { x: 60., y: 90. }, 25.);
+        let overlapping = world.bodies_overlapping_shape(&explosion);
+
+        assert_eq!(overlapping.len(), 2);
+        assert!(matches!(overlapping[0], BodyHandle::Static(_)));
+        assert_eq!(overlapping[1], far.into());
+    }
+
+    #[test]
+    fn test_area_queries_follow_a_moving_body() {
+        let mut world = World::new(ZERO);
+
+        let mut body = create_circle(ZERO, 10.);
+        body.as_mut().velocity = Vec2D { x: 0., y: 1000. };
+        let circle = world.add_dynamic(body);
+
+        let around = |y: f64| BoundingVolume {
+            top_left: Vec2D { x: -5., y: y - 5. },
+            bottom_right: Vec2D { x: 5., y: y + 5. },
+        };
+
+        for _ in 0..3 {
+            world.tick(0.1);
+        }
+
+        assert_eq!(
+            world.bodies_at_point(Vec2D { x: 0., y: 300. }),
+            vec![circle.into()]
+        );
+        assert_eq!(world.bodies_in_aabb(around(300.)), vec![circle.into()]);
+        assert_eq!(world.bodies_in_aabb(around(0.)), vec![]);
+        assert_eq!(
+            world.bodies_overlapping_shape(&create_circle(Vec2D { x: 0., y: 315. }, 10.)),
+            vec![circle.into()]
+        );
+
+        world.get_mut(circle).unwrap().as_mut().position = ZERO;
+
+        assert_eq!(world.bodies_at_point(ZERO), vec![circle.into()]);
+        assert_eq!(world.bodies_in_aabb(around(0.)), vec![circle.into()]);
+        assert_eq!(world.bodies_in_aabb(around(300.)), vec![]);
+        assert_eq!(
+            world.bodies_overlapping_shape(&create_circle(Vec2D { x: 0., y: 300. }, 10.)),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_raycast_finds_the_closest_body() {
+        let mut world = create_world();
+
+        let near = world.add_dynamic(create_circle(Vec2D { x: 0., y: 50. }, 10.));
+        world.add_dynamic(create_circle(Vec2D { x: 0., y: 80. }, 10.));
+
+        let hit = world.raycast(ZERO, UNIT_DOWN, 200., |_| true).unwrap();
+
+        assert_eq!(hit.body, near.into());
+        assert_eq!(hit.point, Vec2D { x: 0., y: 40. });
+        assert_eq!(hit.normal, UNIT_UP);
+        assert_eq!(hit.fraction, 0.2);
+
+        let hit = world
+            .raycast(ZERO, UNIT_DOWN, 200., |body| {
+                !matches!(body, BodyHandle::Dynamic(_))
+            })
+            .unwrap();
+
+        assert!(matches!(hit.body, BodyHandle::Static(_)));
+        assert_eq!(hit.point, Vec2D { x: 0., y: 100. });
+        assert!(world.raycast(ZERO, UNIT_DOWN, 30., |_| true).is_none());
+    }
+
+    #[test]
+    fn test_queries_find_bodies_where_they_have_moved() {
+        let mut world = World::new(ZERO);
+
+        let mut body = create_circle(ZERO, 10.);
+        body.as_mut().velocity = Vec2D { x: 1000., y: 0. };
+        let circle = world.add_dynamic(body);
+
+        world.tick(0.1);
+
+        let position = Vec2D { x: 100., y: 0. };
+
+        assert_eq!(world.bodies_at_point(position), vec![circle.into()]);
+        assert_eq!(
+            world
+                .raycast(Vec2D { x: 100., y: -50. }, UNIT_DOWN, 100., |_| true)
+                .map(|hit| hit.body),
+            Some(circle.into())
+        );
+
+        let position = Vec2D { x: -300., y: 0. };
+        world.get_mut(circle).unwrap().as_mut().position = position;
+
+        assert_eq!(world.bodies_at_point(position), vec![circle.into()]);
+        assert_eq!(world.bodies_at_point(Vec2D { x: 100., y: 0. }), vec![]);
+
+        for (_, body) in world.dynamic_bodies_mut() {
+            body.as_mut().position = ZERO;
+        }
+
+        assert_eq!(world.bodies_at_point(ZERO), vec![circle.into()]);
+        assert_eq!(world.bodies_at_point(position), vec![]);
+    }
+
+    #[test]
+    fn test_cast_shape_stops_at_the_first_hit() {
+        let mut world = create_world();
+
+        let rectangle = world.add_dynamic(DynamicBody::Rectangle(Rectangle::new(
+            BaseDynamicBody::new(Vec2D { x: 100., y: 0. }, ZERO, 0., 1.),
+            10.,
+            10.,
+        )));
+
+        let box_shape = DynamicBody::Rectangle(Rectangle::new(
+            BaseDynamicBody::new(ZERO, ZERO, 0., 1.),
+            5.,
+            5.,
+        ));
+
+        let hit = world
+            .cast_shape(&box_shape, Vec2D { x: 200., y: 0. }, |_| true)
+            .unwrap();
+
+        assert_eq!(hit.body, rectangle.into());
+        assert_eq!(hit.normal, UNIT_LEFT);
+        assert!((hit.fraction * 200. - 85.).abs() <= world.config.slop);
+
+        let hit = world
+            .cast_shape(&create_circle(ZERO, 5.), Vec2D { x: 0., y: 200. }, |_| true)
+            .unwrap();
+
+        assert!(matches!(hit.body, BodyHandle::Static(_)));
+        assert_eq!(hit.normal, UNIT_UP);
+        assert!((hit.point.y - 100.).abs() <= world.config.slop);
+    }
+
+    #[test]
+    fn test_cast_shape_reports_the_part_it_hits() {
+        let mut world = World::new(ZERO);
+
+        // A floor leading to a wall.
+        let chain = world.add_static(StaticBody::Chain(
+            Chain::new(
+                vec![
+                    Vec2D { x: -100., y: 0. },
+                    Vec2D { x: 100., y: 0. },
+                    Vec2D { x: 100., y: -100. },
+                ],
+                false,
+            )
+            .unwrap(),
+        ));
+
+        // Sliding along the floor, which it already touches, into the wall.
+        let hit = world
+            .cast_shape(
+                &create_circle(Vec2D { x: 0., y: -4. }, 5.),
+                Vec2D { x: 200., y: 0. },
+                |_| true,
+            )
+            .unwrap();
+
+        assert_eq!(hit.body, chain.into());
+        assert_eq!(hit.normal, UNIT_LEFT);
+        assert!((hit.fraction * 200. - 95.).abs() <= world.config.slop);
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+
+use super::{BodyHandle, DynamicBodyHandle, StaticBodyHandle, WorldConfig};
+use crate::arena::Arena;
+use crate::body::*;
+use crate::simulation::collisions::*;
+use crate::vec2::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContactKey {
+    this: BodyHandle,
+    /// The part of the first body, e.g. the segment of a chain.
+    part: usize,
+    that: DynamicBodyHandle,
+    feature: u32,
+}
+
+/// The impulses accumulated at a contact point, kept from one tick to the next to warm start
+/// the solver.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AccumulatedImpulse {
+    normal: f64,
+    tangent: f64,
+}
+
+pub type ContactCache = HashMap<ContactKey, AccumulatedImpulse>;
+
+/// A contact between a body (or static body, in which case `this` is `None`) and a dynamic
+/// body, as seen by the solver.
+#[derive(Debug, Clone, Copy)]
+pub struct SolverContact {
+    this_key: BodyHandle,
+    part: usize,
+    this: Option<DynamicBodyHandle>,
+    that: DynamicBodyHandle,
+    manifold: ContactManifold,
+    tangent: Vec2D,
+    this_initial_position: Vec2D,
+    that_initial_position: Vec2D,
+    static_friction: f64,
+    dynamic_friction: f64,
+    points: [SolverContactPoint; MAX_MANIFOLD_POINTS],
+    point_count: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct SolverContactPoint {
+    feature: u32,
+    this_contact_vector: Vec2D,
+    that_contact_vector: Vec2D,
+    normal_mass: f64,
+    tangent_mass: f64,
+    velocity_bias: f64,
+    impulse: AccumulatedImpulse,
+}
+
+impl SolverContact {
+    /// Creates a contact between two dynamic bodies `i` and `j`.
+    pub fn dynamic(
+        manifold: ContactManifold,
+        i: DynamicBodyHandle,
+        j: DynamicBodyHandle,
+        bodies: &Arena<DynamicBody>,
+        config: &WorldConfig,
+    ) -> Self {
+        let this_body = bodies[i].as_ref();
+        let that_body = bodies[j].as_ref();
+
+        Self::new(
+            manifold,
+            BodyHandle::Dynamic(i),
+            Some(this_body),
+            Some(this_body),
+            j,
+            that_body,
+            config,
+        )
+    }
+
+    /// Creates a contact between part `part` of static body `i` and dynamic body `j`.
+    pub fn static_dynamic(
+        manifold: ContactManifold,
+        i: StaticBodyHandle,
+        part: usize,
+        j: DynamicBodyHandle,
+        static_bodies: &Arena<StaticBody>,
+        bodies: &Arena<DynamicBody>,
+        config: &WorldConfig,
+    ) -> Self {
+        Self {
+            part,
+            ..Self::new(
+                manifold,
+                BodyHandle::Static(i),
+                None,
+                static_bodies[i].material(),
+                j,
+                bodies[j].as_ref(),
+                config,
+            )
+        }
+    }
+
+    fn new(
+        manifold: ContactManifold,
+        this_key: BodyHandle,
+        this_body: Option<&BaseDynamicBody>,
+        this_material: Option<&BaseDynamicBody>,
+        that: DynamicBodyHandle,
+        that_body: &BaseDynamicBody,
+        config: &WorldConfig,
+    ) -> Self {
+        let normal = manifold.normal;
+        let tangent = Vec2D {
+            x: -normal.y,
+            y: normal.x,
+        };
+
+        // Lines, segments and chains have no material of their own, so use the dynamic body's.
+        let (coefficient_of_restitution, static_friction, dynamic_friction) = match this_material {
+            Some(this_material) => (
+                config.restitution_combine_rule.combine(
+                    this_material.coefficient_of_restitution,
+                    that_body.coefficient_of_restitution,
+                ),
+                config
+                    .friction_combine_rule
+                    .combine(this_material.static_friction, that_body.static_friction),
+                config
+                    .friction_combine_rule
+                    .combine(this_material.dynamic_friction, that_body.dynamic_friction),
+            ),
+            None => (
+                that_body.coefficient_of_restitution,
+                that_body.static_friction,
+                that_body.dynamic_friction,
+            ),
+        };
+
+        let mut points = [SolverContactPoint::default(); MAX_MANIFOLD_POINTS];
+
+        for (solver_point, point) in points.iter_mut().zip(manifold.points()) {
+            *solver_point = {
+                let this_contact_vector = match this_body {
+                    Some(this_body) => &point.position - &this_body.position,
+                    None => ZERO,
+                };
+                let that_contact_vector = &point.position - &that_body.position;
+
+                let effective_inverse_mass = |direction: &Vec2D| {
+                    let mut result =
+                        get_effective_inverse_mass(direction, &that_contact_vector, that_body);
+
+                    if let Some(this_body) = this_body {
+                        result +=
+                            get_effective_inverse_mass(direction, &this_contact_vector, this_body);
+                    }
+
+                    result
+                };
+
+                let relative_velocity = match this_body {
+                    Some(this_body) => {
+                        &that_body.velocity_at(&that_contact_vector)
+                            - &this_body.velocity_at(&this_contact_vector)
+                    }
+                    None => that_body.velocity_at(&that_contact_vector),
+                };
+
+                let relative_velocity_dot_normal = relative_velocity.dot_product(&normal);
+
+                let velocity_bias =
+                    if relative_velocity_dot_normal < -config.restitution_velocity_threshold {
+                        -coefficient_of_restitution * relative_velocity_dot_normal
+                    } else {
+                        0.
+                    };
+
+                SolverContactPoint {
+                    feature: point.id,
+                    this_contact_vector,
+                    that_contact_vector,
+                    normal_mass: 1. / effective_inverse_mass(&normal),
+                    tangent_mass: 1. / effective_inverse_mass(&tangent),
+                    velocity_bias,
+                    impulse: AccumulatedImpulse::default(),
+                }
+            };
+        }
+
+        let this = match this_key {
+            BodyHandle::Static(_) => None,
+            BodyHandle::Dynamic(i) => Some(i),
+        };
+
+        Self {
+            this_key,
+            part: 0,
+            this,
+            that,
+            manifold,
+            tangent,
+            this_initial_position: this_body.map_or(ZERO, |body| body.position),
+            that_initial_position: that_body.position,
+            static_friction,
+            dynamic_friction,
+            points,
+            point_count: manifold.points().len(),
+        }
+    }
+
+    /// The first body, with the part of it that is touching the second.
+    pub fn this(&self) -> (BodyHandle, usize) {
+        (self.this_key, self.part)
+    }
+
+    pub fn that(&self) -> DynamicBodyHandle {
+        self.that
+    }
+
+    pub fn manifold(&self) -> &ContactManifold {
+        &self.manifold
+    }
+
+    /// The impulse accumulated over all the contact points, i.e. applied to the second body,
+    /// the first one getting the opposite.
+    pub fn total_impulse(&self) -> Vec2D {
+        self.points[..self.point_count]
+            .iter()
+            .fold(ZERO, |total, point| {
+                &(&total + &(&self.manifold.normal * point.impulse.normal))
+                    + &(&self.tangent * point.impulse.tangent)
+            })
+    }
+
+    fn key(&self, point: &SolverContactPoint) -> ContactKey {
+        ContactKey {
+            this: self.this_key,
+            part: self.part,
+            that: self.that,
+            feature: point.feature,
+        }
+    }
+
+    /// Applies the impulses accumulated on the previous tick, if the same contact points existed.
+    pub fn warm_start(&mut self, cache: &ContactCache, bodies: &mut Arena<DynamicBody>) {
+        for i in 0..self.point_count {
+            let Some(impulse) = cache.get(&self.key(&self.points[i])) else {
+                continue;
+            };
+
+            self.points[i].impulse = *impulse;
+
+            let point = self.points[i];
+            let total_impulse =
+                &(&self.manifold.normal * impulse.normal) + &(&self.tangent * impulse.tangent);
+
+            self.apply_impulse(bodies, &point, &total_impulse);
+        }
+    }
+
+    pub fn solve_velocities(&mut self, bodies: &mut Arena<DynamicBody>) {
+        for i in 0..self.point_count {
+            let point = self.points[i];
+
+            // Friction first, so that the non-penetration constraint has the final say.
+            let tangent_velocity = self
+                .relative_velocity(bodies, &point)
+                .dot_product(&self.tangent);
+
+            let static_limit = self.static_friction * point.impulse.normal;
+
+            let mut tangent_impulse = point.impulse.tangent - tangent_velocity * point.tangent_mass;
+
+            // Coulomb's law: if the static friction cannot hold the bodies, they slide.
+            if tangent_impulse.abs() > static_limit {
+                let dynamic_limit = self.dynamic_friction * point.impulse.normal;
+                tangent_impulse = tangent_impulse.clamp(-dynamic_limit, dynamic_limit);
+            }
+
+            let delta = tangent_impulse - point.impulse.tangent;
+            self.points[i].impulse.tangent = tangent_impulse;
+            self.apply_impulse(bodies, &point, &(&self.tangent * delta));
+
+            let normal_velocity = self
+                .relative_velocity(bodies, &point)
+                .dot_product(&self.manifold.normal);
+
+            let normal_impulse = (point.impulse.normal
+                - (normal_velocity - point.velocity_bias) * point.normal_mass)
+                .max(0.);
+
+            let delta = normal_impulse - point.impulse.normal;
+            self.points[i].impulse.normal = normal_impulse;
+            self.apply_impulse(bodies, &point, &(&self.manifold.normal * delta));
+        }
+    }
+
+    /// Pushes the bodies apart along the contact normal, by a fraction of their overlap.
+    ///
+    /// The overlap is estimated from how far the bodies have moved since the contact was
+    /// detected, so that this can be called repeatedly.
+    pub fn correct_positions(&self, bodies: &mut Arena<DynamicBody>, config: &WorldConfig) {
+        let this_body = self.this.map(|i| bodies[i].as_ref());
+        let that_body = bodies[self.that].as_ref();
+
+        let this_inverse_mass = this_body.map_or(0., |body| body.inverse_mass);
+        let that_inverse_mass = that_body.inverse_mass;
+
+        let this_displacement =
+            this_body.map_or(ZERO, |body| &body.position - &self.this_initial_position);
+        let that_displacement = &that_body.position - &self.that_initial_position;
+
+        let distance = self.manifold.deepest_point().distance
+            + (&that_displacement - &this_displacement).dot_product(&self.manifold.normal);
+
+        let correction_amount = (distance + config.slop).min(0.) * config.baumgarte_factor
+            / (this_inverse_mass + that_inverse_mass);
+
+        let correction = &self.manifold.normal * correction_amount;
+
+        if let Some(i) = self.this {
+            bodies[i].as_mut().position += &(&correction * this_inverse_mass);
+        }
+
+        bodies[self.that].as_mut().position -= &(&correction * that_inverse_mass);
+    }
+
+    /// Records the accumulated impulses, to warm start the next tick.
+    pub fn store_impulses(&self, cache: &mut ContactCache) {
+        for point in &self.points[..self.point_count] {
+            cache.insert(self.key(point), point.impulse);
+        }
+    }
+
+    fn relative_velocity(&self, bodies: &Arena<DynamicBody>, point: &SolverContactPoint) -> Vec2D {
+        let that_velocity = bodies[self.that]
+            .as_ref()
+            .velocity_at(&point.that_contact_vector);
+
+        match self.this {
+            Some(i) => &that_velocity - &bodies[i].as_ref().velocity_at(&point.this_contact_vector),
+            None => that_velocity,
+        }
+    }
+
+    /// Applies `impulse` to the second body, and the opposite impulse to the first.
+    fn apply_impulse(
+        &self,
+        bodies: &mut Arena<DynamicBody>,
+        point: &SolverContactPoint,
+        impulse: &Vec2D,
+    ) {
+        if let Some(i) = self.this {
+            bodies[i]
+                .as_mut()
+                .apply_impulse(&-impulse, &point.this_contact_vector);
+        }
+
+        bodies[self.that]
+            .as_mut()
+            .apply_impulse(impulse, &point.that_contact_vector);
+    }
+}
+
+/// The inverse of the mass that `body` appears to have when pushed along `direction` at
+/// `contact_vector`.
+fn get_effective_inverse_mass(
+    direction: &Vec2D,
+    contact_vector: &Vec2D,
+    body: &BaseDynamicBody,
+) -> f64 {
+    let angular_factor = contact_vector.cross_product(direction);
+
+    body.inverse_mass + angular_factor * angular_factor * body.inverse_moment_of_inertia
+}
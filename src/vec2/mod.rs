@@ -1,6 +1,6 @@
 mod arithmetic;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
 pub struct Vec2D {
     pub x: f64,
     pub y: f64,
@@ -52,4 +52,63 @@ impl Vec2D {
             y: self.y.clamp(min.y, max.y),
         }
     }
+
+    /// The z component of the 3D cross product of `self` and `other`.
+    pub fn cross_product(&self, other: &Self) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// The cross product of a scalar angular velocity (around the z axis) with `self`.
+    pub fn scalar_cross_product(scalar: f64, vector: &Self) -> Self {
+        Vec2D {
+            x: -scalar * vector.y,
+            y: scalar * vector.x,
+        }
+    }
+
+    pub fn rotate(&self, angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+
+        Vec2D {
+            x: self.x * cos - self.y * sin,
+            y: self.x * sin + self.y * cos,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn assert_close(actual: Vec2D, expected: Vec2D) {
+        assert!(
+            (&actual - &expected).length() < 1e-9,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_cross_product_works() {
+        assert_eq!(UNIT_RIGHT.cross_product(&UNIT_DOWN), 1.);
+        assert_eq!(UNIT_DOWN.cross_product(&UNIT_RIGHT), -1.);
+        assert_eq!(UNIT_RIGHT.cross_product(&UNIT_LEFT), 0.);
+    }
+
+    #[test]
+    fn test_scalar_cross_product_is_consistent_with_cross_product() {
+        let r = Vec2D { x: 3., y: -2. };
+        let v = Vec2D::scalar_cross_product(2., &r);
+
+        assert_eq!(v.dot_product(&r), 0.);
+        assert_eq!(r.cross_product(&v), 2. * r.length_squared());
+    }
+
+    #[test]
+    fn test_rotate_works() {
+        assert_close(UNIT_RIGHT.rotate(FRAC_PI_2), UNIT_DOWN);
+        assert_close(UNIT_DOWN.rotate(FRAC_PI_2), UNIT_LEFT);
+        assert_close(UNIT_RIGHT.rotate(-FRAC_PI_2), UNIT_UP);
+    }
 }